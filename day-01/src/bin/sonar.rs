@@ -0,0 +1,206 @@
+use day_01::*;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+const USAGE: &str = "Usage: sonar [--window N] [--input <path|->] [--format text|json] [--moving-average N] [--anomaly-threshold N]";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Format {
+    Text,
+    /// One JSON object per line, so the samples can be written while reading the input
+    Json,
+}
+
+#[derive(Debug)]
+struct Args {
+    window: usize,
+    input: String,
+    format: Format,
+    moving_average: Option<usize>,
+    anomaly_threshold: Option<u32>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            window: 1,
+            input: "./input.txt".to_string(),
+            format: Format::Text,
+            moving_average: None,
+            anomaly_threshold: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Expected a value for {}", arg));
+
+            match arg.as_str() {
+                "--window" => parsed.window = parse_number(&arg, value()?)?,
+                "--input" => parsed.input = value()?,
+                "--format" => {
+                    parsed.format = match value()?.as_str() {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        other => return Err(format!("Invalid format {}", other)),
+                    }
+                }
+                "--moving-average" => parsed.moving_average = Some(parse_number(&arg, value()?)?),
+                "--anomaly-threshold" => {
+                    parsed.anomaly_threshold = Some(parse_number(&arg, value()?)?)
+                }
+                other => return Err(format!("Unknown argument {}", other)),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn open_input(&self) -> Result<Box<dyn BufRead>, String> {
+        if self.input == "-" {
+            Ok(Box::new(io::stdin().lock()))
+        } else {
+            let file = File::open(&self.input)
+                .map_err(|e| format!("Failed to open {}: {}", self.input, e))?;
+
+            Ok(Box::new(BufReader::new(file)))
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Expected a number for {}, found {}", arg, value))
+}
+
+#[derive(Serialize)]
+struct Output<'a> {
+    window: usize,
+    increments: u64,
+    #[serde(flatten)]
+    summary: &'a SonarSummary,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Record<'a> {
+    Sample(&'a Sample),
+    Summary(Output<'a>),
+}
+
+fn print_json(record: &Record) -> Result<(), String> {
+    println!(
+        "{}",
+        serde_json::to_string(record).map_err(|e| e.to_string())?
+    );
+
+    Ok(())
+}
+
+fn print_sample(sample: &Sample) {
+    if let Some(average) = sample.moving_average {
+        println!("Moving average at {}: {}", sample.index, average);
+    }
+
+    if let Some(anomaly) = &sample.anomaly {
+        println!(
+            "Anomaly at {}: {} -> {} (jump {})",
+            anomaly.index,
+            anomaly.previous,
+            anomaly.current,
+            anomaly.jump()
+        );
+    }
+}
+
+fn print_text(output: &Output) {
+    let Output {
+        window,
+        increments,
+        summary,
+    } = output;
+
+    println!("Increments (window {}): {}", window, increments);
+    println!("Measurements: {}", summary.measurements);
+
+    for changes in &summary.changes {
+        println!(
+            "Changes (window {}): {} increases, {} decreases, {} flat",
+            changes.window, changes.increases, changes.decreases, changes.flat
+        );
+    }
+
+    let runs = [
+        ("increasing", summary.longest_increasing),
+        ("decreasing", summary.longest_decreasing),
+    ];
+
+    for (name, run) in runs {
+        match run {
+            Some(run) => println!(
+                "Longest {} run: {}..={} ({} measurements)",
+                name,
+                run.start,
+                run.end,
+                run.length()
+            ),
+            None => println!("Longest {} run: none", name),
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut windows = vec![args.window];
+    if args.window != 1 {
+        windows.insert(0, 1);
+    }
+
+    let options = ReportOptions {
+        windows,
+        moving_average: args.moving_average,
+        anomaly_threshold: args.anomaly_threshold,
+    };
+    let mut analyzer = SonarAnalyzer::new(&options)?;
+
+    for measurement in parse_measurements(args.open_input()?) {
+        let sample = analyzer.push(measurement?);
+
+        if sample.moving_average.is_some() || sample.anomaly.is_some() {
+            match args.format {
+                Format::Text => print_sample(&sample),
+                Format::Json => print_json(&Record::Sample(&sample))?,
+            }
+        }
+    }
+
+    let summary = analyzer.finish();
+    let output = Output {
+        window: args.window,
+        increments: summary
+            .changes_for(args.window)
+            .map_or(0, |changes| changes.increases),
+        summary: &summary,
+    };
+
+    match args.format {
+        Format::Text => print_text(&output),
+        Format::Json => print_json(&Record::Summary(output))?,
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
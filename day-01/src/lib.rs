@@ -1,14 +1,31 @@
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::path::Path;
 
-pub fn read_input() -> impl Iterator<Item = u32> {
-    File::open("./input.txt")
-        .map(|file| BufReader::new(file))
-        .unwrap()
-        .lines()
-        .map(Result::unwrap)
-        .map(|line| line.parse::<u32>().unwrap())
+pub use crate::report::{
+    Anomaly, Changes, ReportOptions, Run, Sample, SonarAnalyzer, SonarReport, SonarSummary,
+};
+
+mod report;
+
+pub fn read_input(
+    path: impl AsRef<Path>,
+) -> Result<impl Iterator<Item = Result<u32, String>>, String> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+    Ok(parse_measurements(BufReader::new(file)))
+}
+
+pub fn parse_measurements(reader: impl BufRead) -> impl Iterator<Item = Result<u32, String>> {
+    reader.lines().enumerate().map(|(i, line)| {
+        let line = line.map_err(|e| format!("Failed to read line {}: {}", i + 1, e))?;
+
+        line.trim()
+            .parse::<u32>()
+            .map_err(|_| format!("Expected a measurement on line {}, found {:?}", i + 1, line))
+    })
 }
 
 pub fn count_increments(mut measurements: impl Iterator<Item = u32>) -> u32 {
@@ -19,7 +36,7 @@ pub fn count_increments(mut measurements: impl Iterator<Item = u32>) -> u32 {
         return 0;
     };
 
-    while let Some(next) = measurements.next() {
+    for next in measurements {
         if next > current {
             increments += 1;
         }
@@ -41,8 +58,8 @@ impl<T: Clone, I: Iterator<Item = T>, const WINDOW_SIZE: usize> Windows<T, I, WI
     pub fn new(mut iter: I) -> Self {
         let mut current_window: [Option<T>; WINDOW_SIZE] = [Self::INIT; WINDOW_SIZE];
 
-        for i in 0..WINDOW_SIZE - 1 {
-            current_window[i] = iter.next();
+        for slot in current_window.iter_mut().take(WINDOW_SIZE - 1) {
+            *slot = iter.next();
         }
 
         Self {
@@ -54,9 +71,9 @@ impl<T: Clone, I: Iterator<Item = T>, const WINDOW_SIZE: usize> Windows<T, I, WI
     fn prepare_return_value(&self) -> Option<[T; WINDOW_SIZE]> {
         let mut return_value: [Option<T>; WINDOW_SIZE] = [Self::INIT; WINDOW_SIZE];
 
-        for i in 0..WINDOW_SIZE {
-            if let Some(value) = &self.current_window[i] {
-                return_value[i] = Some(value.clone());
+        for (slot, value) in return_value.iter_mut().zip(&self.current_window) {
+            if let Some(value) = value {
+                *slot = Some(value.clone());
             } else {
                 return None;
             }
@@ -144,6 +161,17 @@ mod tests {
         assert_eq!(increments, 5);
     }
 
+    #[test]
+    fn parse_measurements_reports_line() {
+        let input = "199\n200\nabc\n".as_bytes();
+        let measurements: Result<Vec<u32>, String> = parse_measurements(input).collect();
+
+        assert_eq!(
+            measurements,
+            Err("Expected a measurement on line 3, found \"abc\"".to_string())
+        );
+    }
+
     #[test]
     fn windows_works() {
         let vec = vec![1, 2, 3, 4, 5];
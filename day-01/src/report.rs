@@ -0,0 +1,433 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReportOptions {
+    /// Window sizes for which increases, decreases and flat steps are counted
+    pub windows: Vec<usize>,
+    /// Window size of the moving average series, `None` disables the series
+    pub moving_average: Option<usize>,
+    /// Jumps between consecutive measurements strictly above this value are reported as anomalies
+    pub anomaly_threshold: Option<u32>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            windows: vec![1, 3],
+            moving_average: None,
+            anomaly_threshold: None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct Changes {
+    pub window: usize,
+    pub increases: u64,
+    pub decreases: u64,
+    pub flat: u64,
+}
+
+impl Changes {
+    fn new(window: usize) -> Self {
+        Self {
+            window,
+            increases: 0,
+            decreases: 0,
+            flat: 0,
+        }
+    }
+}
+
+/// A run of strictly monotonic measurements, `start` and `end` are inclusive indices
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct Run {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Run {
+    pub fn length(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct Anomaly {
+    pub index: usize,
+    pub previous: u32,
+    pub current: u32,
+}
+
+impl Anomaly {
+    pub fn jump(&self) -> u32 {
+        self.previous.abs_diff(self.current)
+    }
+}
+
+/// The series values completed by a single measurement
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize)]
+pub struct Sample {
+    pub index: usize,
+    /// The average of the moving average window ending at this measurement
+    pub moving_average: Option<f64>,
+    pub anomaly: Option<Anomaly>,
+}
+
+/// The statistics of a measurement stream which take constant space
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct SonarSummary {
+    pub measurements: usize,
+    pub changes: Vec<Changes>,
+    pub longest_increasing: Option<Run>,
+    pub longest_decreasing: Option<Run>,
+}
+
+impl SonarSummary {
+    pub fn changes_for(&self, window: usize) -> Option<&Changes> {
+        self.changes.iter().find(|changes| changes.window == window)
+    }
+}
+
+/// A [`SonarSummary`] together with the collected series of every [`Sample`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SonarReport {
+    #[serde(flatten)]
+    pub summary: SonarSummary,
+    pub moving_averages: Vec<f64>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl SonarReport {
+    pub fn new(
+        measurements: impl IntoIterator<Item = u32>,
+        options: &ReportOptions,
+    ) -> Result<Self, String> {
+        let mut analyzer = SonarAnalyzer::new(options)?;
+        let mut moving_averages = vec![];
+        let mut anomalies = vec![];
+
+        for measurement in measurements {
+            let sample = analyzer.push(measurement);
+            moving_averages.extend(sample.moving_average);
+            anomalies.extend(sample.anomaly);
+        }
+
+        Ok(Self {
+            summary: analyzer.finish(),
+            moving_averages,
+            anomalies,
+        })
+    }
+
+    pub fn changes_for(&self, window: usize) -> Option<&Changes> {
+        self.summary.changes_for(window)
+    }
+}
+
+#[derive(Debug, Default)]
+struct RunTracker {
+    start: usize,
+    longest: Option<Run>,
+}
+
+impl RunTracker {
+    fn extend(&mut self, index: usize) {
+        let run = Run {
+            start: self.start,
+            end: index,
+        };
+
+        if self
+            .longest
+            .is_none_or(|longest| run.length() > longest.length())
+        {
+            self.longest = Some(run);
+        }
+    }
+
+    fn restart(&mut self, index: usize) {
+        self.start = index;
+    }
+}
+
+/// Computes a [`SonarSummary`] in a single pass, keeping only as many measurements as the largest window needs
+///
+/// The moving averages and anomalies aren't kept, every [`push`](Self::push) returns the ones it completes.
+#[derive(Debug)]
+pub struct SonarAnalyzer {
+    options: ReportOptions,
+    history: VecDeque<u32>,
+    history_size: usize,
+    index: usize,
+    changes: Vec<Changes>,
+    increasing: RunTracker,
+    decreasing: RunTracker,
+    moving_sum: u64,
+}
+
+impl SonarAnalyzer {
+    pub fn new(options: &ReportOptions) -> Result<Self, String> {
+        if options.windows.contains(&0) {
+            return Err("Window size must be at least 1".to_string());
+        }
+
+        if options.moving_average == Some(0) {
+            return Err("Moving average window must be at least 1".to_string());
+        }
+
+        let history_size = options
+            .windows
+            .iter()
+            .copied()
+            .chain(options.moving_average)
+            .chain(Some(1))
+            .max()
+            .unwrap_or(1)
+            + 1;
+
+        Ok(Self {
+            options: options.clone(),
+            history: VecDeque::with_capacity(history_size),
+            history_size,
+            index: 0,
+            changes: options.windows.iter().copied().map(Changes::new).collect(),
+            increasing: RunTracker::default(),
+            decreasing: RunTracker::default(),
+            moving_sum: 0,
+        })
+    }
+
+    /// Returns the measurement `back` positions before the latest one
+    fn lookback(&self, back: usize) -> Option<u32> {
+        self.history
+            .len()
+            .checked_sub(back + 1)
+            .map(|i| self.history[i])
+    }
+
+    pub fn push(&mut self, measurement: u32) -> Sample {
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+        self.history.push_back(measurement);
+
+        let index = self.index;
+        self.index += 1;
+
+        let mut sample = Sample {
+            index,
+            ..Sample::default()
+        };
+
+        for changes in &mut self.changes {
+            // Consecutive window sums only differ by the first and the last measurement
+            let earlier = self
+                .history
+                .len()
+                .checked_sub(changes.window + 1)
+                .map(|i| self.history[i]);
+
+            if let Some(earlier) = earlier {
+                if measurement > earlier {
+                    changes.increases += 1;
+                } else if measurement < earlier {
+                    changes.decreases += 1;
+                } else {
+                    changes.flat += 1;
+                }
+            }
+        }
+
+        if let Some(previous) = self.lookback(1) {
+            if measurement > previous {
+                self.increasing.extend(index);
+                self.decreasing.restart(index);
+            } else if measurement < previous {
+                self.decreasing.extend(index);
+                self.increasing.restart(index);
+            } else {
+                self.increasing.restart(index);
+                self.decreasing.restart(index);
+            }
+
+            if let Some(threshold) = self.options.anomaly_threshold {
+                if previous.abs_diff(measurement) > threshold {
+                    sample.anomaly = Some(Anomaly {
+                        index,
+                        previous,
+                        current: measurement,
+                    });
+                }
+            }
+        }
+
+        if let Some(window) = self.options.moving_average {
+            self.moving_sum += measurement as u64;
+
+            if let Some(dropped) = self.lookback(window) {
+                self.moving_sum -= dropped as u64;
+            }
+
+            if index + 1 >= window {
+                sample.moving_average = Some(self.moving_sum as f64 / window as f64);
+            }
+        }
+
+        sample
+    }
+
+    pub fn finish(self) -> SonarSummary {
+        SonarSummary {
+            measurements: self.index,
+            changes: self.changes,
+            longest_increasing: self.increasing.longest,
+            longest_decreasing: self.decreasing.longest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_increments, three_measurement_sums};
+
+    const EXAMPLE: [u32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn changes_match_puzzle_answers() {
+        let report = SonarReport::new(EXAMPLE, &ReportOptions::default()).unwrap();
+
+        assert_eq!(
+            report.changes_for(1),
+            Some(&Changes {
+                window: 1,
+                increases: 7,
+                decreases: 2,
+                flat: 0
+            })
+        );
+        assert_eq!(report.changes_for(3).unwrap().increases, 5);
+        assert_eq!(report.summary.measurements, 10);
+    }
+
+    #[test]
+    fn changes_match_window_sums() {
+        let measurements = [5, 3, 3, 8, 1, 1, 1, 9, 2, 7, 7, 0];
+        let options = ReportOptions {
+            windows: vec![3],
+            ..ReportOptions::default()
+        };
+        let report = SonarReport::new(measurements, &options).unwrap();
+        let increases = count_increments(three_measurement_sums(measurements.into_iter()));
+
+        assert_eq!(report.changes_for(3).unwrap().increases, increases as u64);
+    }
+
+    #[test]
+    fn longest_runs() {
+        let report = SonarReport::new(EXAMPLE, &ReportOptions::default()).unwrap();
+
+        assert_eq!(
+            report.summary.longest_increasing,
+            Some(Run { start: 0, end: 3 })
+        );
+        assert_eq!(
+            report.summary.longest_decreasing,
+            Some(Run { start: 3, end: 4 })
+        );
+    }
+
+    #[test]
+    fn no_runs_when_flat() {
+        let report = SonarReport::new([4, 4, 4], &ReportOptions::default()).unwrap();
+
+        assert_eq!(report.summary.longest_increasing, None);
+        assert_eq!(report.summary.longest_decreasing, None);
+        assert_eq!(report.changes_for(1).unwrap().flat, 2);
+    }
+
+    #[test]
+    fn moving_averages() {
+        let options = ReportOptions {
+            moving_average: Some(2),
+            ..ReportOptions::default()
+        };
+        let report = SonarReport::new([1, 3, 5, 6], &options).unwrap();
+
+        assert_eq!(report.moving_averages, vec![2.0, 4.0, 5.5]);
+    }
+
+    #[test]
+    fn anomalies() {
+        let options = ReportOptions {
+            anomaly_threshold: Some(20),
+            ..ReportOptions::default()
+        };
+        let report = SonarReport::new(EXAMPLE, &options).unwrap();
+
+        assert_eq!(
+            report.anomalies,
+            vec![
+                Anomaly {
+                    index: 6,
+                    previous: 207,
+                    current: 240
+                },
+                Anomaly {
+                    index: 7,
+                    previous: 240,
+                    current: 269
+                },
+            ]
+        );
+        assert_eq!(report.anomalies[0].jump(), 33);
+    }
+
+    #[test]
+    fn push_returns_completed_samples() {
+        let options = ReportOptions {
+            moving_average: Some(2),
+            anomaly_threshold: Some(1),
+            ..ReportOptions::default()
+        };
+        let mut analyzer = SonarAnalyzer::new(&options).unwrap();
+
+        assert_eq!(analyzer.push(1), Sample::default());
+        assert_eq!(
+            analyzer.push(3),
+            Sample {
+                index: 1,
+                moving_average: Some(2.0),
+                anomaly: Some(Anomaly {
+                    index: 1,
+                    previous: 1,
+                    current: 3
+                }),
+            }
+        );
+        assert_eq!(analyzer.push(4).anomaly, None);
+        assert_eq!(analyzer.finish().measurements, 3);
+    }
+
+    #[test]
+    fn report_serializes_flat() {
+        let report = SonarReport::new([1, 2], &ReportOptions::default()).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["measurements"], 2);
+        assert_eq!(json["changes"][0]["increases"], 1);
+        assert!(json["moving_averages"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_empty_window() {
+        let options = ReportOptions {
+            windows: vec![0],
+            ..ReportOptions::default()
+        };
+
+        assert!(SonarReport::new(EXAMPLE, &options).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::Position;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 600.0;
+const MARGIN: f64 = 60.0;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Svg,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "svg" => Ok(ExportFormat::Svg),
+            format => Err(format!("Invalid export format {}", format)),
+        }
+    }
+}
+
+pub fn export(
+    format: ExportFormat,
+    positions: impl IntoIterator<Item = Position>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match format {
+        ExportFormat::Csv => write_csv(positions, out),
+        ExportFormat::Svg => write_svg(positions, out),
+    }
+}
+
+/// Writes one row per position, numbering the steps in the order they were produced
+pub fn write_csv(
+    positions: impl IntoIterator<Item = Position>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "step,horizontal,depth,aim")?;
+
+    for (step, position) in positions.into_iter().enumerate() {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            step, position.horizontal_position, position.depth, position.aim
+        )?;
+    }
+
+    Ok(())
+}
+
+struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+impl Bounds {
+    fn of(values: impl Iterator<Item = i32>) -> Self {
+        values.fold(Self { min: 0, max: 0 }, |bounds, value| Self {
+            min: bounds.min.min(value),
+            max: bounds.max.max(value),
+        })
+    }
+
+    /// Maps `value` onto `0.0..=length`
+    fn scale(&self, value: i32, length: f64) -> f64 {
+        let range = (self.max as f64 - self.min as f64).max(1.0);
+
+        (value as f64 - self.min as f64) / range * length
+    }
+}
+
+/// Writes a standalone SVG document with the route as a polyline, depth pointing down
+pub fn write_svg(
+    positions: impl IntoIterator<Item = Position>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let positions: Vec<Position> = positions.into_iter().collect();
+    let horizontal = Bounds::of(positions.iter().map(|p| p.horizontal_position));
+    let depth = Bounds::of(positions.iter().map(|p| p.depth));
+
+    let left = MARGIN;
+    let top = MARGIN;
+    let right = WIDTH - MARGIN;
+    let bottom = HEIGHT - MARGIN;
+
+    let points: Vec<String> = positions
+        .iter()
+        .map(|p| {
+            let x = left + horizontal.scale(p.horizontal_position, right - left);
+            let y = top + depth.scale(p.depth, bottom - top);
+            format!("{:.2},{:.2}", x, y)
+        })
+        .collect();
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = WIDTH,
+        h = HEIGHT
+    )?;
+    writeln!(out, r#"  <rect width="100%" height="100%" fill="white"/>"#)?;
+    writeln!(
+        out,
+        r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black"/>"#,
+        left, top, right, top
+    )?;
+    writeln!(
+        out,
+        r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black"/>"#,
+        left, top, left, bottom
+    )?;
+    writeln!(
+        out,
+        r#"  <text x="{}" y="{}" text-anchor="middle">horizontal position</text>"#,
+        (left + right) / 2.0,
+        top - 30.0
+    )?;
+    writeln!(
+        out,
+        r#"  <text x="{x}" y="{y}" text-anchor="middle" transform="rotate(-90 {x} {y})">depth</text>"#,
+        x = left - 30.0,
+        y = (top + bottom) / 2.0
+    )?;
+    writeln!(
+        out,
+        r#"  <text x="{}" y="{}" text-anchor="start">{}</text>"#,
+        left,
+        top - 8.0,
+        horizontal.min
+    )?;
+    writeln!(
+        out,
+        r#"  <text x="{}" y="{}" text-anchor="end">{}</text>"#,
+        right,
+        top - 8.0,
+        horizontal.max
+    )?;
+    writeln!(
+        out,
+        r#"  <text x="{}" y="{}" text-anchor="end">{}</text>"#,
+        left - 8.0,
+        top,
+        depth.min
+    )?;
+    writeln!(
+        out,
+        r#"  <text x="{}" y="{}" text-anchor="end">{}</text>"#,
+        left - 8.0,
+        bottom,
+        depth.max
+    )?;
+    writeln!(
+        out,
+        r#"  <polyline fill="none" stroke="steelblue" stroke-width="2" points="{}"/>"#,
+        points.join(" ")
+    )?;
+    writeln!(out, "</svg>")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{trajectory, Instruction, Part2};
+
+    fn example() -> impl Iterator<Item = Position> {
+        let instructions: Vec<Instruction> = [
+            "forward 5",
+            "down 5",
+            "forward 8",
+            "up 3",
+            "down 8",
+            "forward 2",
+        ]
+        .into_iter()
+        .map(|line| line.parse().unwrap())
+        .collect();
+
+        std::iter::once(Position::default())
+            .chain(trajectory::<Part2, _>(instructions).map(Result::unwrap))
+    }
+
+    fn exported(format: ExportFormat) -> String {
+        let mut out = vec![];
+        export(format, example(), &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_matches_golden_file() {
+        assert_eq!(
+            exported(ExportFormat::Csv),
+            include_str!("../golden/example.csv")
+        );
+    }
+
+    #[test]
+    fn svg_matches_golden_file() {
+        assert_eq!(
+            exported(ExportFormat::Svg),
+            include_str!("../golden/example.svg")
+        );
+    }
+}
@@ -1,9 +1,18 @@
+use std::marker::PhantomData;
 use std::str::FromStr;
 
+pub use crate::export::{export, write_csv, write_svg, ExportFormat};
+pub use crate::program::{Instructions, Program, Statement, SyntaxError};
+
+mod export;
+mod program;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Direction {
     Up,
     Down,
     Forward,
+    Back,
 }
 
 impl FromStr for Direction {
@@ -14,11 +23,13 @@ impl FromStr for Direction {
             "up" => Ok(Direction::Up),
             "down" => Ok(Direction::Down),
             "forward" => Ok(Direction::Forward),
+            "back" => Ok(Direction::Back),
             variant => Err(format!("Invalid direction {}", variant)),
         }
     }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Instruction {
     pub direction: Direction,
     pub amount: i32,
@@ -28,10 +39,12 @@ impl FromStr for Instruction {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split(" ");
+        let mut split = s.split(' ');
 
-        let direction = split.next().ok_or("Expected direction".to_string())?;
-        let amount = split.next().ok_or("Expected amount".to_string())?;
+        let direction = split
+            .next()
+            .ok_or_else(|| "Expected direction".to_string())?;
+        let amount = split.next().ok_or_else(|| "Expected amount".to_string())?;
 
         let instruction = Self {
             direction: direction.parse()?,
@@ -48,37 +61,165 @@ pub fn parse_instructions(
     lines.into_iter().map(|line| line.parse())
 }
 
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
 pub struct Position {
     pub horizontal_position: i32,
     pub depth: i32,
+    pub aim: i32,
 }
 
-impl FromIterator<Instruction> for Position {
-    fn from_iter<T: IntoIterator<Item = Instruction>>(iter: T) -> Self {
-        let mut horizontal_position = 0;
-        let mut depth = 0;
-        let mut aim = 0;
-
-        for instruction in iter {
-            let Instruction { direction, amount } = instruction;
-
-            match direction {
-                Direction::Down => aim += amount,
-                Direction::Up => aim -= amount,
-                Direction::Forward => {
-                    horizontal_position += amount;
-                    depth += aim * amount;
-                }
+impl Position {
+    /// Leaves the position unchanged if any part of it would overflow
+    pub fn apply<I: Interpreter>(&mut self, instruction: &Instruction) -> Result<(), String> {
+        let mut next = *self;
+        I::execute(&mut next, instruction)?;
+        *self = next;
+
+        Ok(())
+    }
+
+    pub fn product(&self) -> i64 {
+        self.horizontal_position as i64 * self.depth as i64
+    }
+}
+
+/// Defines how an [`Instruction`] changes the [`Position`] of the submarine
+pub trait Interpreter {
+    /// Fails if any part of the position overflows
+    fn execute(position: &mut Position, instruction: &Instruction) -> Result<(), String>;
+}
+
+fn checked(value: Option<i32>, name: &str) -> Result<i32, String> {
+    value.ok_or_else(|| format!("The {} overflows", name))
+}
+
+/// `down` and `up` change the depth directly, the aim stays at 0
+pub struct Part1;
+
+impl Interpreter for Part1 {
+    fn execute(position: &mut Position, instruction: &Instruction) -> Result<(), String> {
+        let Instruction { direction, amount } = *instruction;
+        let Position {
+            horizontal_position,
+            depth,
+            ..
+        } = *position;
+
+        match direction {
+            Direction::Down => position.depth = checked(depth.checked_add(amount), "depth")?,
+            Direction::Up => position.depth = checked(depth.checked_sub(amount), "depth")?,
+            Direction::Forward => {
+                position.horizontal_position = checked(
+                    horizontal_position.checked_add(amount),
+                    "horizontal position",
+                )?
+            }
+            Direction::Back => {
+                position.horizontal_position = checked(
+                    horizontal_position.checked_sub(amount),
+                    "horizontal position",
+                )?
             }
         }
 
-        Self {
+        Ok(())
+    }
+}
+
+/// `down` and `up` change the aim, `forward` and `back` move along it
+pub struct Part2;
+
+impl Interpreter for Part2 {
+    fn execute(position: &mut Position, instruction: &Instruction) -> Result<(), String> {
+        let Instruction { direction, amount } = *instruction;
+        let Position {
             horizontal_position,
             depth,
+            aim,
+        } = *position;
+
+        match direction {
+            Direction::Down => position.aim = checked(aim.checked_add(amount), "aim")?,
+            Direction::Up => position.aim = checked(aim.checked_sub(amount), "aim")?,
+            Direction::Forward => {
+                position.horizontal_position = checked(
+                    horizontal_position.checked_add(amount),
+                    "horizontal position",
+                )?;
+                position.depth = checked(
+                    aim.checked_mul(amount)
+                        .and_then(|change| depth.checked_add(change)),
+                    "depth",
+                )?;
+            }
+            Direction::Back => {
+                position.horizontal_position = checked(
+                    horizontal_position.checked_sub(amount),
+                    "horizontal position",
+                )?;
+                position.depth = checked(
+                    aim.checked_mul(amount)
+                        .and_then(|change| depth.checked_sub(change)),
+                    "depth",
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fails with the first instruction that makes the position overflow
+pub fn run<I: Interpreter>(
+    instructions: impl IntoIterator<Item = Instruction>,
+) -> Result<Position, String> {
+    trajectory::<I, _>(instructions)
+        .last()
+        .unwrap_or(Ok(Position::default()))
+}
+
+/// Returns the position after every instruction, ending with an error if the position overflows
+pub fn trajectory<I: Interpreter, T: IntoIterator<Item = Instruction>>(
+    instructions: T,
+) -> Trajectory<I, T::IntoIter> {
+    Trajectory {
+        instructions: instructions.into_iter(),
+        position: Some(Position::default()),
+        interpreter: PhantomData,
+    }
+}
+
+pub struct Trajectory<I, T> {
+    instructions: T,
+    /// `None` once the position overflowed
+    position: Option<Position>,
+    interpreter: PhantomData<I>,
+}
+
+impl<I: Interpreter, T: Iterator<Item = Instruction>> Iterator for Trajectory<I, T> {
+    type Item = Result<Position, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.position.as_mut()?;
+        let instruction = self.instructions.next()?;
+
+        match position.apply::<I>(&instruction) {
+            Ok(()) => Some(Ok(*position)),
+            Err(e) => {
+                self.position = None;
+                Some(Err(e))
+            }
         }
     }
 }
 
+/// Panics if the position overflows, [`run`] reports it as an error instead
+impl FromIterator<Instruction> for Position {
+    fn from_iter<T: IntoIterator<Item = Instruction>>(iter: T) -> Self {
+        run::<Part2>(iter).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +242,122 @@ mod tests {
         assert_eq!(position.horizontal_position, 15);
         assert_eq!(position.depth, 60);
     }
+
+    fn example() -> Vec<Instruction> {
+        [
+            "forward 5",
+            "down 5",
+            "forward 8",
+            "up 3",
+            "down 8",
+            "forward 2",
+        ]
+        .into_iter()
+        .map(|line| line.parse().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn run_part_1() {
+        let position = run::<Part1>(example()).unwrap();
+
+        assert_eq!(position.horizontal_position, 15);
+        assert_eq!(position.depth, 10);
+        assert_eq!(position.aim, 0);
+        assert_eq!(position.product(), 150);
+    }
+
+    #[test]
+    fn run_part_2() {
+        let position = run::<Part2>(example()).unwrap();
+
+        assert_eq!(position.product(), 900);
+        assert_eq!(position.aim, 10);
+    }
+
+    #[test]
+    fn back_reverses_forward() {
+        let instructions = ["down 3", "forward 4", "back 4"].map(|line| line.parse().unwrap());
+
+        assert_eq!(
+            run::<Part1>(instructions),
+            Ok(Position {
+                horizontal_position: 0,
+                depth: 3,
+                aim: 0
+            })
+        );
+        assert_eq!(
+            run::<Part2>(instructions),
+            Ok(Position {
+                horizontal_position: 0,
+                depth: 0,
+                aim: 3
+            })
+        );
+    }
+
+    #[test]
+    fn trajectory_works() {
+        let positions: Vec<(i32, i32, i32)> = trajectory::<Part2, _>(example())
+            .map(|p| p.unwrap())
+            .map(|p| (p.horizontal_position, p.depth, p.aim))
+            .collect();
+
+        assert_eq!(
+            positions,
+            vec![
+                (5, 0, 0),
+                (5, 0, 5),
+                (13, 40, 5),
+                (13, 40, 2),
+                (13, 40, 10),
+                (15, 60, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let instructions = ["down 3", "forward 100", "forward 1000000000", "down 1"]
+            .map(|line| line.parse().unwrap());
+        let positions: Vec<Result<Position, String>> =
+            trajectory::<Part2, _>(instructions).collect();
+
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[2], Err("The depth overflows".to_string()));
+        assert_eq!(
+            run::<Part2>(instructions),
+            Err("The depth overflows".to_string())
+        );
+        assert_eq!(
+            run::<Part1>(instructions).map(|position| position.product()),
+            Ok(4_000_000_400)
+        );
+
+        let mut position = positions[1].clone().unwrap();
+        assert!(position.apply::<Part2>(&instructions[2]).is_err());
+        assert_eq!(Ok(position), positions[1]);
+    }
+
+    #[test]
+    fn trajectory_ends_after_overflow() {
+        let instructions = ["up 2147483647", "up 2", "down 1"].map(|line| line.parse().unwrap());
+        let positions: Vec<Result<Position, String>> =
+            trajectory::<Part1, _>(instructions).collect();
+
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[1], Err("The depth overflows".to_string()));
+    }
+
+    #[test]
+    fn product_does_not_overflow() {
+        let position = Position {
+            horizontal_position: i32::MAX,
+            depth: i32::MAX,
+            aim: 0,
+        };
+
+        assert_eq!(position.product(), i32::MAX as i64 * i32::MAX as i64);
+    }
 }
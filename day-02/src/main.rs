@@ -1,14 +1,95 @@
-use day_02::{parse_instructions, Position};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use day_02::{
+    export, run, trajectory, ExportFormat, Interpreter, Part1, Part2, Position, Program,
+    SyntaxError,
+};
+use std::fs;
+use std::io;
+
+const USAGE: &str = "Usage: day-02 [--export csv|svg] [--part 1|2]";
+
+struct Args {
+    export: Option<ExportFormat>,
+    part: u8,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            export: None,
+            part: 2,
+        };
+
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("Expected a value for {}", arg))?;
+
+            match arg.as_str() {
+                "--export" => parsed.export = Some(value.parse()?),
+                "--part" => {
+                    parsed.part = match value.as_str() {
+                        "1" => 1,
+                        "2" => 2,
+                        part => return Err(format!("Invalid part {}", part)),
+                    }
+                }
+                other => return Err(format!("Unknown argument {}", other)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Exports the positions up to the first overflow, then reports it
+fn export_trajectory<I: Interpreter>(
+    format: ExportFormat,
+    program: &Program,
+) -> Result<(), String> {
+    let mut overflow = None;
+    let positions = trajectory::<I, _>(program.instructions())
+        .map_while(|position| position.map_err(|e| overflow = Some(e)).ok());
+
+    export(
+        format,
+        std::iter::once(Position::default()).chain(positions),
+        &mut io::stdout().lock(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    overflow.map_or(Ok(()), Err)
+}
 
 fn main() {
-    let lines = File::open("./input.txt")
-        .map(BufReader::new)
-        .unwrap()
-        .lines()
-        .map(Result::unwrap);
-    let position: Position = parse_instructions(lines).map(Result::unwrap).collect();
-
-    println!("{}", position.horizontal_position * position.depth);
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    if let Err(e) = solve(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn solve(args: Args) -> Result<(), String> {
+    let program: Program = fs::read_to_string("./input.txt")
+        .map_err(|e| e.to_string())?
+        .parse()
+        .map_err(|e: SyntaxError| e.to_string())?;
+
+    match args.export {
+        Some(format) if args.part == 1 => export_trajectory::<Part1>(format, &program),
+        Some(format) => export_trajectory::<Part2>(format, &program),
+        None => {
+            println!(
+                "[Part 1] {}",
+                run::<Part1>(program.instructions())?.product()
+            );
+            println!(
+                "[Part 2] {}",
+                run::<Part2>(program.instructions())?.product()
+            );
+
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,384 @@
+use crate::{Direction, Instruction};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl SyntaxError {
+    fn new(position: Span, message: impl Into<String>) -> Self {
+        Self {
+            line: position.line,
+            column: position.column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Statement {
+    Instruction(Instruction),
+    Repeat { times: usize, body: Vec<Statement> },
+}
+
+/// A parsed submarine script
+///
+/// Besides the plain `<direction> <amount>` commands, scripts may contain `#` comments,
+/// blank lines and (nested) `repeat <times> { ... }` blocks.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Program {
+    pub statements: Vec<Statement>,
+}
+
+impl Program {
+    /// Returns the instructions of the program with all repeat blocks unrolled lazily
+    pub fn instructions(&self) -> Instructions<'_> {
+        Instructions {
+            stack: vec![Frame {
+                body: &self.statements,
+                index: 0,
+                remaining: 1,
+            }],
+        }
+    }
+}
+
+impl FromStr for Program {
+    type Err = SyntaxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens: tokens.into_iter(),
+            end: end_of(s),
+        };
+        let statements = parser.statements(None)?;
+
+        Ok(Self { statements })
+    }
+}
+
+struct Frame<'a> {
+    body: &'a [Statement],
+    index: usize,
+    remaining: usize,
+}
+
+pub struct Instructions<'a> {
+    stack: Vec<Frame<'a>>,
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Instruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+
+            if frame.index == frame.body.len() {
+                frame.remaining -= 1;
+                frame.index = 0;
+
+                if frame.remaining == 0 || frame.body.is_empty() {
+                    self.stack.pop();
+                }
+
+                continue;
+            }
+
+            let statement = &frame.body[frame.index];
+            frame.index += 1;
+
+            match statement {
+                Statement::Instruction(instruction) => return Some(*instruction),
+                Statement::Repeat { times, body } => {
+                    if *times > 0 {
+                        self.stack.push(Frame {
+                            body,
+                            index: 0,
+                            remaining: *times,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Span {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Number(String),
+    Open,
+    Close,
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
+
+fn end_of(s: &str) -> Span {
+    let line = s.lines().count().max(1);
+    let column = s.lines().last().map_or(0, |last| last.chars().count()) + 1;
+
+    Span { line, column }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, SyntaxError> {
+    let mut tokens = vec![];
+
+    for (line_index, line) in s.lines().enumerate() {
+        let mut chars = line.chars().enumerate().peekable();
+
+        while let Some((column_index, c)) = chars.next() {
+            let span = Span {
+                line: line_index + 1,
+                column: column_index + 1,
+            };
+
+            let kind = match c {
+                '#' => break,
+                '{' => TokenKind::Open,
+                '}' => TokenKind::Close,
+                c if c.is_whitespace() => continue,
+                c if c.is_alphabetic() || c.is_ascii_digit() || c == '-' => {
+                    let mut text = c.to_string();
+
+                    while let Some((_, c)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+                        text.push(c);
+                    }
+
+                    if c.is_alphabetic() {
+                        TokenKind::Word(text)
+                    } else {
+                        TokenKind::Number(text)
+                    }
+                }
+                c => {
+                    return Err(SyntaxError::new(
+                        span,
+                        format!("Unexpected character '{}'", c),
+                    ))
+                }
+            };
+
+            tokens.push(Token { kind, span });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+    end: Span,
+}
+
+impl Parser {
+    /// Parses statements until the end of input or, inside a block, until the closing brace
+    fn statements(&mut self, block: Option<Span>) -> Result<Vec<Statement>, SyntaxError> {
+        let mut statements = vec![];
+
+        loop {
+            let token = match self.tokens.next() {
+                Some(token) => token,
+                None => {
+                    return match block {
+                        Some(open) => Err(SyntaxError::new(open, "Unclosed '{'")),
+                        None => Ok(statements),
+                    }
+                }
+            };
+
+            let statement = match token.kind {
+                TokenKind::Close if block.is_some() => return Ok(statements),
+                TokenKind::Close => return Err(SyntaxError::new(token.span, "Unexpected '}'")),
+                TokenKind::Open => return Err(SyntaxError::new(token.span, "Unexpected '{'")),
+                TokenKind::Number(number) => {
+                    return Err(SyntaxError::new(
+                        token.span,
+                        format!("Expected command, found {}", number),
+                    ))
+                }
+                TokenKind::Word(word) if word == "repeat" => self.repeat(token.span)?,
+                TokenKind::Word(word) => {
+                    let direction =
+                        Direction::from_str(&word).map_err(|e| SyntaxError::new(token.span, e))?;
+                    let amount = self.number(&word)?;
+
+                    Statement::Instruction(Instruction { direction, amount })
+                }
+            };
+
+            statements.push(statement);
+        }
+    }
+
+    fn repeat(&mut self, span: Span) -> Result<Statement, SyntaxError> {
+        let times = self.number("repeat")?;
+        let times = usize::try_from(times)
+            .map_err(|_| SyntaxError::new(span, "Repeat count must not be negative"))?;
+
+        match self.tokens.next() {
+            Some(Token {
+                kind: TokenKind::Open,
+                span,
+            }) => {
+                let body = self.statements(Some(span))?;
+
+                Ok(Statement::Repeat { times, body })
+            }
+            Some(token) => Err(SyntaxError::new(
+                token.span,
+                "Expected '{' after repeat count",
+            )),
+            None => Err(SyntaxError::new(
+                self.end,
+                "Expected '{' after repeat count",
+            )),
+        }
+    }
+
+    fn number(&mut self, after: &str) -> Result<i32, SyntaxError> {
+        match self.tokens.next() {
+            Some(Token {
+                kind: TokenKind::Number(number),
+                span,
+            }) => number
+                .parse()
+                .map_err(|_| SyntaxError::new(span, format!("Invalid number {}", number))),
+            Some(token) => Err(SyntaxError::new(
+                token.span,
+                format!("Expected number after '{}'", after),
+            )),
+            None => Err(SyntaxError::new(
+                self.end,
+                format!("Expected number after '{}'", after),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{run, Part1, Part2};
+
+    fn instruction(direction: Direction, amount: i32) -> Instruction {
+        Instruction { direction, amount }
+    }
+
+    #[test]
+    fn parses_plain_commands() {
+        let program: Program = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2"
+            .parse()
+            .unwrap();
+
+        assert_eq!(run::<Part2>(program.instructions()).unwrap().product(), 900);
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let source = "# route\n\nforward 1 # go\n   \nback 2\n";
+        let program: Program = source.parse().unwrap();
+
+        assert_eq!(
+            program.instructions().collect::<Vec<_>>(),
+            vec![
+                instruction(Direction::Forward, 1),
+                instruction(Direction::Back, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn unrolls_nested_repeats() {
+        let source = "
+            repeat 2 {
+                down 1
+                repeat 3 { forward 2 }
+            }
+            repeat 0 { up 100 }
+            repeat 4 {}
+        ";
+        let program: Program = source.parse().unwrap();
+        let instructions: Vec<Instruction> = program.instructions().collect();
+
+        assert_eq!(instructions.len(), 8);
+        assert_eq!(instructions[0], instruction(Direction::Down, 1));
+        assert_eq!(instructions[4], instruction(Direction::Down, 1));
+
+        let position = run::<Part1>(program.instructions()).unwrap();
+        assert_eq!((position.horizontal_position, position.depth), (12, 2));
+    }
+
+    #[test]
+    fn repeats_report_overflow() {
+        let program: Program = "repeat 100000 { down 100000 forward 1 }".parse().unwrap();
+
+        assert_eq!(
+            run::<Part2>(program.instructions()),
+            Err("The depth overflows".to_string())
+        );
+        assert_eq!(
+            run::<Part1>(program.instructions()),
+            Err("The depth overflows".to_string())
+        );
+    }
+
+    #[test]
+    fn reports_unknown_command() {
+        let error = "forward 1\n  sideways 3".parse::<Program>().unwrap_err();
+
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.to_string(), "2:3: Invalid direction sideways");
+    }
+
+    #[test]
+    fn reports_missing_amount() {
+        let error = "up\n".parse::<Program>().unwrap_err();
+
+        assert_eq!(error.to_string(), "1:3: Expected number after 'up'");
+    }
+
+    #[test]
+    fn reports_unclosed_block() {
+        let error = "repeat 2 {\n  forward 1\n".parse::<Program>().unwrap_err();
+
+        assert_eq!(error.to_string(), "1:10: Unclosed '{'");
+    }
+
+    #[test]
+    fn reports_unexpected_close() {
+        let error = "forward 1 }".parse::<Program>().unwrap_err();
+
+        assert_eq!((error.line, error.column), (1, 11));
+    }
+
+    #[test]
+    fn reports_missing_block() {
+        let error = "repeat 2 forward 1".parse::<Program>().unwrap_err();
+
+        assert_eq!(error.to_string(), "1:10: Expected '{' after repeat count");
+    }
+}
@@ -0,0 +1,37 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day_03::{count_ones, count_ones_scalar, Number};
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+fn generate(len: usize, width: usize) -> Vec<Number> {
+    let mut runner = TestRunner::deterministic();
+    let mask = Number::MAX >> (Number::BITS as usize - width);
+
+    (0..len)
+        .map(|_| any::<Number>().new_tree(&mut runner).unwrap().current() & mask)
+        .collect()
+}
+
+pub fn column_counting_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("column counting");
+    group.sample_size(10);
+
+    for width in [12, 64, 128] {
+        let numbers = generate(2_000_000, width);
+
+        group.bench_with_input(BenchmarkId::new("scalar", width), &numbers, |b, numbers| {
+            b.iter(|| count_ones_scalar(black_box(numbers), width))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("bit-sliced", width),
+            &numbers,
+            |b, numbers| b.iter(|| count_ones(black_box(numbers), width)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benchmarks, column_counting_bench);
+criterion_main!(benchmarks);
@@ -0,0 +1,137 @@
+use crate::Number;
+
+/// Number of bit-sliced counter planes, every lane can count up to `2^PLANES - 1`
+const PLANES: usize = 16;
+
+/// Numbers counted per chunk before the counter planes are flushed
+pub const CHUNK_SIZE: usize = (1 << PLANES) - 1;
+
+/// Counts the ones at every bit position below `width` by walking `numbers` once per position
+pub fn count_ones_scalar(numbers: &[Number], width: usize) -> Vec<usize> {
+    (0..width)
+        .map(|position| {
+            numbers
+                .iter()
+                .filter(|number| *number >> position & 1 == 1)
+                .count()
+        })
+        .collect()
+}
+
+/// Counts the ones at every bit position below `width` in a single pass over `numbers`
+///
+/// Each chunk is added into bit-sliced counters: plane `k` holds bit `k` of the count of every bit
+/// position, so adding a number is a ripple carry over a few planes instead of one step per bit
+/// position. Chunks are counted in parallel with the `rayon` feature.
+pub fn count_ones(numbers: &[Number], width: usize) -> Vec<usize> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        numbers
+            .par_chunks(CHUNK_SIZE)
+            .map(|chunk| count_chunk(chunk, width))
+            .reduce(|| vec![0; width], add)
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        numbers
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| count_chunk(chunk, width))
+            .fold(vec![0; width], add)
+    }
+}
+
+fn count_chunk(chunk: &[Number], width: usize) -> Vec<usize> {
+    debug_assert!(chunk.len() <= CHUNK_SIZE);
+
+    let mut planes: [Number; PLANES] = [0; PLANES];
+
+    for number in chunk {
+        let mut carry = *number;
+
+        for plane in &mut planes {
+            if carry == 0 {
+                break;
+            }
+
+            let sum = *plane ^ carry;
+            carry &= *plane;
+            *plane = sum;
+        }
+    }
+
+    (0..width)
+        .map(|position| {
+            planes
+                .iter()
+                .enumerate()
+                .map(|(k, plane)| ((plane >> position & 1) as usize) << k)
+                .sum()
+        })
+        .collect()
+}
+
+fn add(mut a: Vec<usize>, b: Vec<usize>) -> Vec<usize> {
+    for (a, b) in a.iter_mut().zip(b) {
+        *a += b;
+    }
+
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    fn random_numbers(len: usize, width: usize) -> Vec<Number> {
+        let mut runner = TestRunner::deterministic();
+        let mask = Number::MAX >> (Number::BITS as usize - width);
+
+        (0..len)
+            .map(|_| any::<Number>().new_tree(&mut runner).unwrap().current() & mask)
+            .collect()
+    }
+
+    #[test]
+    fn counts_example() {
+        let numbers: Vec<Number> = vec![
+            0b_00100, 0b_11110, 0b_10110, 0b_10111, 0b_10101, 0b_01111, 0b_00111, 0b_11100,
+            0b_10000, 0b_11001, 0b_00010, 0b_01010,
+        ];
+
+        assert_eq!(count_ones(&numbers, 5), vec![5, 7, 8, 5, 7]);
+    }
+
+    #[test]
+    fn matches_scalar_counts() {
+        for width in [1, 5, 12, 63, 64, 65, 128] {
+            for len in [0, 1, 1000] {
+                let numbers = random_numbers(len, width);
+
+                assert_eq!(
+                    count_ones(&numbers, width),
+                    count_ones_scalar(&numbers, width)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_scalar_counts_across_chunks() {
+        let numbers = random_numbers(2 * CHUNK_SIZE + 17, 128);
+
+        assert_eq!(count_ones(&numbers, 128), count_ones_scalar(&numbers, 128));
+    }
+
+    #[test]
+    fn counts_full_chunk_of_ones() {
+        let numbers = vec![Number::MAX; CHUNK_SIZE + 1];
+
+        assert_eq!(count_ones(&numbers, 128), vec![CHUNK_SIZE + 1; 128]);
+    }
+}
@@ -0,0 +1,407 @@
+use crate::{Number, MAX_WIDTH};
+use std::collections::HashMap;
+
+/// The order in which bit positions are examined
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+/// Which candidates are kept at each bit position
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Rule {
+    /// Keep the candidates sharing the most common bit
+    Majority,
+    /// Keep the candidates sharing the least common bit
+    Minority,
+}
+
+/// Which candidates are kept when both bit values are equally common
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Tie {
+    KeepOne,
+    KeepZero,
+}
+
+/// When to stop examining further bit positions
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Stop {
+    /// Stop as soon as at most this many candidates remain
+    AtMost(usize),
+    /// Examine every bit position
+    AllBits,
+}
+
+/// The outcome of [`BitCriteria::select`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Selection {
+    /// The remaining candidates in ascending order with respect to the bit order
+    pub survivors: Vec<Number>,
+    /// The number of remaining candidates after each examined bit position
+    pub trace: Vec<usize>,
+}
+
+impl Selection {
+    pub fn value(&self) -> Option<Number> {
+        self.survivors.first().copied()
+    }
+}
+
+/// Builder for a majority vote filter over the bits of a list of numbers
+///
+/// Defaults to the oxygen generator criteria: most significant bit first, keep the most common
+/// bit, keep ones on ties and stop once a single candidate remains. A bit value nobody shares is
+/// never selected, so filtering can't eliminate all candidates.
+///
+/// # Examples
+///
+/// ```
+/// use day_03::{BitCriteria, Rule, Tie};
+///
+/// let numbers = [0b_00100, 0b_11110, 0b_10110, 0b_10111, 0b_10101, 0b_01111];
+/// let selection = BitCriteria::new(5)
+///     .rule(Rule::Minority)
+///     .tie(Tie::KeepZero)
+///     .select(&numbers);
+///
+/// assert_eq!(selection.value(), Some(0b_00100));
+/// assert_eq!(selection.trace, vec![2, 1]);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BitCriteria {
+    width: usize,
+    order: BitOrder,
+    rule: Rule,
+    tie: Tie,
+    stop: Stop,
+}
+
+impl BitCriteria {
+    pub fn new(width: usize) -> Self {
+        assert!(
+            width <= MAX_WIDTH,
+            "At most {} bits are supported",
+            MAX_WIDTH
+        );
+
+        Self {
+            width,
+            order: BitOrder::MsbFirst,
+            rule: Rule::Majority,
+            tie: Tie::KeepOne,
+            stop: Stop::AtMost(1),
+        }
+    }
+
+    pub fn order(self, order: BitOrder) -> Self {
+        Self { order, ..self }
+    }
+
+    pub fn rule(self, rule: Rule) -> Self {
+        Self { rule, ..self }
+    }
+
+    pub fn tie(self, tie: Tie) -> Self {
+        Self { tie, ..self }
+    }
+
+    pub fn stop(self, stop: Stop) -> Self {
+        Self { stop, ..self }
+    }
+
+    fn positions(&self) -> Vec<usize> {
+        match self.order {
+            BitOrder::MsbFirst => (0..self.width).rev().collect(),
+            BitOrder::LsbFirst => (0..self.width).collect(),
+        }
+    }
+
+    fn choose(&self, zeros: usize, ones: usize) -> usize {
+        if zeros == 0 {
+            return 1;
+        }
+
+        if ones == 0 {
+            return 0;
+        }
+
+        if zeros == ones {
+            return match self.tie {
+                Tie::KeepOne => 1,
+                Tie::KeepZero => 0,
+            };
+        }
+
+        let common = (ones > zeros) as usize;
+
+        match self.rule {
+            Rule::Majority => common,
+            Rule::Minority => 1 - common,
+        }
+    }
+
+    /// Filters `numbers` in O(n·bits) by walking down a bit-trie of the candidates
+    ///
+    /// Bits above `width` aren't examined, but they're kept in the survivors.
+    pub fn select(&self, numbers: &[Number]) -> Selection {
+        let trie = BitTrie::new(numbers, self.positions());
+        let mut node = BitTrie::ROOT;
+        let mut depth = 0;
+        let mut trace = vec![];
+
+        while depth < trie.positions.len() && trie.count(Some(node)) > 0 {
+            if let Stop::AtMost(limit) = self.stop {
+                if trie.count(Some(node)) <= limit {
+                    break;
+                }
+            }
+
+            let zeros = trie.count(trie.child(node, 0));
+            let ones = trie.count(trie.child(node, 1));
+            let bit = self.choose(zeros, ones);
+
+            node = trie.child(node, bit).expect("Chosen side is never empty");
+            depth += 1;
+            trace.push(trie.count(Some(node)));
+        }
+
+        Selection {
+            survivors: trie.values(node, depth),
+            trace,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct Node {
+    /// Index of the child for bit value 0 and 1, [`BitTrie::ROOT`] marks a missing child
+    children: [usize; 2],
+    count: usize,
+}
+
+#[derive(Debug)]
+struct BitTrie {
+    nodes: Vec<Node>,
+    positions: Vec<usize>,
+    /// The numbers ending at each leaf in input order, since they may differ in unexamined bits
+    leaves: HashMap<usize, Vec<Number>>,
+}
+
+impl BitTrie {
+    const ROOT: usize = 0;
+
+    fn new(numbers: &[Number], positions: Vec<usize>) -> Self {
+        let mut nodes = vec![Node::default()];
+        let mut leaves: HashMap<usize, Vec<Number>> = HashMap::new();
+
+        for number in numbers {
+            let mut node = Self::ROOT;
+            nodes[node].count += 1;
+
+            for position in &positions {
+                let bit = (number >> position & 1) as usize;
+                let mut child = nodes[node].children[bit];
+
+                if child == Self::ROOT {
+                    child = nodes.len();
+                    nodes.push(Node::default());
+                    nodes[node].children[bit] = child;
+                }
+
+                node = child;
+                nodes[node].count += 1;
+            }
+
+            leaves.entry(node).or_default().push(*number);
+        }
+
+        Self {
+            nodes,
+            positions,
+            leaves,
+        }
+    }
+
+    fn child(&self, node: usize, bit: usize) -> Option<usize> {
+        Some(self.nodes[node].children[bit]).filter(|child| *child != Self::ROOT)
+    }
+
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.nodes[node].count)
+    }
+
+    /// Collects the numbers stored below `node`, which sits at `depth`
+    fn values(&self, node: usize, depth: usize) -> Vec<Number> {
+        let mut values = vec![];
+        let mut stack = vec![(node, depth)];
+
+        while let Some((node, depth)) = stack.pop() {
+            if depth == self.positions.len() {
+                values.extend(self.leaves.get(&node).into_iter().flatten());
+                continue;
+            }
+
+            if let Some(one) = self.child(node, 1) {
+                stack.push((one, depth + 1));
+            }
+
+            if let Some(zero) = self.child(node, 0) {
+                stack.push((zero, depth + 1));
+            }
+        }
+
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const EXAMPLE: [Number; 12] = [
+        0b_00100, 0b_11110, 0b_10110, 0b_10111, 0b_10101, 0b_01111, 0b_00111, 0b_11100, 0b_10000,
+        0b_11001, 0b_00010, 0b_01010,
+    ];
+
+    /// The straightforward `retain` based filter the trie replaces
+    fn reference(criteria: &BitCriteria, numbers: &[Number]) -> Selection {
+        let mut survivors = numbers.to_vec();
+        let mut trace = vec![];
+
+        for position in criteria.positions() {
+            if survivors.is_empty() {
+                break;
+            }
+
+            if let Stop::AtMost(limit) = criteria.stop {
+                if survivors.len() <= limit {
+                    break;
+                }
+            }
+
+            let ones = survivors.iter().filter(|n| *n >> position & 1 == 1).count();
+            let bit = criteria.choose(survivors.len() - ones, ones) as Number;
+
+            survivors.retain(|n| n >> position & 1 == bit);
+            trace.push(survivors.len());
+        }
+
+        let key = |n: &Number| -> Number {
+            criteria
+                .positions()
+                .iter()
+                .fold(0, |key, position| key << 1 | (n >> position & 1))
+        };
+        survivors.sort_by_key(key);
+
+        Selection { survivors, trace }
+    }
+
+    #[test]
+    fn oxygen_generator_trace() {
+        let selection = BitCriteria::new(5).select(&EXAMPLE);
+
+        assert_eq!(selection.value(), Some(23));
+        assert_eq!(selection.trace, vec![7, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn co2_scrubber_trace() {
+        let selection = BitCriteria::new(5)
+            .rule(Rule::Minority)
+            .tie(Tie::KeepZero)
+            .select(&EXAMPLE);
+
+        assert_eq!(selection.value(), Some(10));
+        assert_eq!(selection.trace, vec![5, 2, 1]);
+    }
+
+    #[test]
+    fn stops_at_limit() {
+        let selection = BitCriteria::new(5).stop(Stop::AtMost(4)).select(&EXAMPLE);
+
+        assert_eq!(
+            selection.survivors,
+            vec![0b_10000, 0b_10101, 0b_10110, 0b_10111]
+        );
+        assert_eq!(selection.trace, vec![7, 4]);
+    }
+
+    #[test]
+    fn keeps_duplicates_when_examining_all_bits() {
+        let selection = BitCriteria::new(3)
+            .stop(Stop::AllBits)
+            .select(&[0b_101, 0b_101, 0b_001]);
+
+        assert_eq!(selection.survivors, vec![0b_101, 0b_101]);
+        assert_eq!(selection.trace, vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn never_selects_an_empty_side() {
+        let selection = BitCriteria::new(3)
+            .rule(Rule::Minority)
+            .select(&[0b_111, 0b_110]);
+
+        assert_eq!(selection.value(), Some(0b_111));
+        assert_eq!(selection.trace, vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn keeps_bits_above_width() {
+        assert_eq!(
+            BitCriteria::new(3).select(&[0b_1000]).value(),
+            Some(0b_1000)
+        );
+
+        let selection = BitCriteria::new(2)
+            .stop(Stop::AllBits)
+            .select(&[0b_110, 0b_010, 0b_001]);
+
+        assert_eq!(selection.survivors, vec![0b_110, 0b_010]);
+    }
+
+    #[test]
+    fn empty_input() {
+        let selection = BitCriteria::new(8).select(&[]);
+
+        assert_eq!(selection.value(), None);
+        assert!(selection.trace.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn matches_reference_implementation(
+            width in 1..=12_usize,
+            numbers in prop::collection::vec(any::<Number>(), 0..40)
+        ) {
+            // Keep two bits above the width, which are never examined
+            let numbers: Vec<Number> =
+                numbers.into_iter().map(|n| n & ((1 << (width + 2)) - 1)).collect();
+
+            for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+                for rule in [Rule::Majority, Rule::Minority] {
+                    for tie in [Tie::KeepOne, Tie::KeepZero] {
+                        for stop in [Stop::AtMost(1), Stop::AtMost(3), Stop::AllBits] {
+                            let criteria = BitCriteria::new(width)
+                                .order(order)
+                                .rule(rule)
+                                .tie(tie)
+                                .stop(stop);
+
+                            prop_assert_eq!(
+                                criteria.select(&numbers),
+                                reference(&criteria, &numbers),
+                                "{:?} {:?}",
+                                criteria,
+                                numbers
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
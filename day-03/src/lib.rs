@@ -0,0 +1,209 @@
+pub use crate::counting::{count_ones, count_ones_scalar, CHUNK_SIZE};
+pub use crate::criteria::{BitCriteria, BitOrder, Rule, Selection, Stop, Tie};
+
+mod counting;
+mod criteria;
+
+pub type Number = u128;
+
+pub const MAX_WIDTH: usize = Number::BITS as usize;
+
+fn oxygen_generator_rating(numbers: &[Number], significant_bits: usize) -> Number {
+    BitCriteria::new(significant_bits)
+        .select(numbers)
+        .value()
+        .expect("Expected at least one number")
+}
+
+fn co2_scrubber_rating(numbers: &[Number], significant_bits: usize) -> Number {
+    BitCriteria::new(significant_bits)
+        .rule(Rule::Minority)
+        .tie(Tie::KeepZero)
+        .select(numbers)
+        .value()
+        .expect("Expected at least one number")
+}
+
+/// The submarine's diagnostic report, a list of binary numbers of equal width
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiagnosticReport {
+    numbers: Vec<Number>,
+    width: usize,
+}
+
+impl DiagnosticReport {
+    /// Parses one binary number per line, inferring the bit width from the first line
+    ///
+    /// Blank lines are skipped.
+    pub fn parse<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Result<Self, String> {
+        let mut numbers = vec![];
+        let mut width = None;
+
+        for (i, line) in lines.into_iter().enumerate() {
+            let line = line.as_ref().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let expected = *width.get_or_insert(line.len());
+
+            if line.len() != expected {
+                return Err(format!(
+                    "Line {} has {} bits, expected {}",
+                    i + 1,
+                    line.len(),
+                    expected
+                ));
+            }
+
+            if line.len() > MAX_WIDTH {
+                return Err(format!(
+                    "Line {} has {} bits, at most {} are supported",
+                    i + 1,
+                    line.len(),
+                    MAX_WIDTH
+                ));
+            }
+
+            let number = Number::from_str_radix(line, 2)
+                .map_err(|_| format!("Line {} is not a binary number: {}", i + 1, line))?;
+
+            numbers.push(number);
+        }
+
+        let width = width.ok_or_else(|| "Diagnostic report is empty".to_string())?;
+
+        Ok(Self { numbers, width })
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    fn mask(&self) -> Number {
+        Number::MAX >> (MAX_WIDTH - self.width)
+    }
+
+    /// Most common bit per position, `1` on ties
+    pub fn gamma_rate(&self) -> Number {
+        count_ones(&self.numbers, self.width)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, ones)| ones * 2 >= self.numbers.len())
+            .fold(0, |gamma, (position, _)| gamma | 1 << position)
+    }
+
+    /// Least common bit per position, `0` on ties
+    pub fn epsilon_rate(&self) -> Number {
+        !self.gamma_rate() & self.mask()
+    }
+
+    /// Returns `None` if the product doesn't fit into a [`Number`]
+    pub fn power_consumption(&self) -> Option<Number> {
+        self.gamma_rate().checked_mul(self.epsilon_rate())
+    }
+
+    pub fn oxygen_generator_rating(&self) -> Number {
+        oxygen_generator_rating(&self.numbers, self.width)
+    }
+
+    pub fn co2_scrubber_rating(&self) -> Number {
+        co2_scrubber_rating(&self.numbers, self.width)
+    }
+
+    /// Returns `None` if the product doesn't fit into a [`Number`]
+    pub fn life_support_rating(&self) -> Option<Number> {
+        self.oxygen_generator_rating()
+            .checked_mul(self.co2_scrubber_rating())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [&str; 12] = [
+        "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000", "11001",
+        "00010", "01010",
+    ];
+
+    #[test]
+    fn oxygen_generator_rating_works() {
+        let numbers: Vec<Number> = vec![
+            0b_00100, 0b_11110, 0b_10110, 0b_10111, 0b_10101, 0b_01111, 0b_00111, 0b_11100,
+            0b_10000, 0b_11001, 0b_00010, 0b_01010,
+        ];
+
+        assert_eq!(oxygen_generator_rating(&numbers, 5), 23);
+    }
+
+    #[test]
+    fn co2_scrubber_rating_works() {
+        let numbers: Vec<Number> = vec![
+            0b_00100, 0b_11110, 0b_10110, 0b_10111, 0b_10101, 0b_01111, 0b_00111, 0b_11100,
+            0b_10000, 0b_11001, 0b_00010, 0b_01010,
+        ];
+
+        assert_eq!(co2_scrubber_rating(&numbers, 5), 10);
+    }
+
+    #[test]
+    fn report_answers_both_parts() {
+        let report = DiagnosticReport::parse(EXAMPLE).unwrap();
+
+        assert_eq!(report.width(), 5);
+        assert_eq!(report.gamma_rate(), 22);
+        assert_eq!(report.epsilon_rate(), 9);
+        assert_eq!(report.power_consumption(), Some(198));
+        assert_eq!(report.oxygen_generator_rating(), 23);
+        assert_eq!(report.co2_scrubber_rating(), 10);
+        assert_eq!(report.life_support_rating(), Some(230));
+    }
+
+    #[test]
+    fn report_supports_128_bits() {
+        let ones = "1".repeat(128);
+        let zeros = "0".repeat(128);
+        let report =
+            DiagnosticReport::parse([ones.as_str(), ones.as_str(), zeros.as_str()]).unwrap();
+
+        assert_eq!(report.width(), 128);
+        assert_eq!(report.gamma_rate(), Number::MAX);
+        assert_eq!(report.epsilon_rate(), 0);
+        assert_eq!(report.oxygen_generator_rating(), Number::MAX);
+        assert_eq!(report.co2_scrubber_rating(), 0);
+    }
+
+    #[test]
+    fn report_rejects_empty_input() {
+        assert_eq!(
+            DiagnosticReport::parse(Vec::<String>::new()),
+            Err("Diagnostic report is empty".to_string())
+        );
+    }
+
+    #[test]
+    fn report_rejects_inconsistent_widths() {
+        assert_eq!(
+            DiagnosticReport::parse(["0101", "011", "1111"]),
+            Err("Line 2 has 3 bits, expected 4".to_string())
+        );
+    }
+
+    #[test]
+    fn report_rejects_too_wide_input() {
+        let line = "1".repeat(129);
+
+        assert!(DiagnosticReport::parse([line]).is_err());
+    }
+
+    #[test]
+    fn report_rejects_non_binary_input() {
+        assert!(DiagnosticReport::parse(["0121"]).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day_04::{BingoGame, Board, Field};
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+/// The board as it was before marking used a lookup table: every draw scans all fields and every
+/// win check scans all rows and columns
+mod scanning {
+    const BOARD_SIZE: usize = 5;
+
+    #[derive(Copy, Clone)]
+    pub struct Field {
+        pub number: u32,
+        pub marked: bool,
+    }
+
+    #[derive(Clone)]
+    pub struct Board {
+        pub rows: [[Field; BOARD_SIZE]; BOARD_SIZE],
+    }
+
+    impl Board {
+        pub fn new(numbers: &[u32]) -> Self {
+            let mut rows = [[Field {
+                number: 0,
+                marked: false,
+            }; BOARD_SIZE]; BOARD_SIZE];
+
+            for (i, number) in numbers.iter().enumerate() {
+                rows[i / BOARD_SIZE][i % BOARD_SIZE].number = *number;
+            }
+
+            Self { rows }
+        }
+
+        pub fn mark_field(&mut self, number: u32) {
+            for row in &mut self.rows {
+                for field in row {
+                    if field.number == number {
+                        field.marked = true;
+                    }
+                }
+            }
+        }
+
+        pub fn has_won(&self) -> bool {
+            self.rows
+                .iter()
+                .any(|row| row.iter().all(|field| field.marked))
+                || (0..BOARD_SIZE).any(|column| self.rows.iter().all(|row| row[column].marked))
+        }
+
+        pub fn sum_of_unmarked(&self) -> u32 {
+            self.rows
+                .iter()
+                .flatten()
+                .filter(|field| !field.marked)
+                .map(|field| field.number)
+                .sum()
+        }
+    }
+
+    /// Plays until every board has won, returning the scores in winning order
+    pub fn play(drawn_numbers: &[u32], mut boards: Vec<Board>) -> Vec<u32> {
+        let mut scores = vec![];
+
+        for number in drawn_numbers {
+            for board in &mut boards {
+                board.mark_field(*number);
+            }
+
+            boards.retain(|board| {
+                if board.has_won() {
+                    scores.push(board.sum_of_unmarked() * number);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        scores
+    }
+}
+
+/// Generates boards with 25 distinct numbers out of `0..numbers` and a shuffled draw of all of them
+fn generate(boards: usize, numbers: u32) -> (Vec<u32>, Vec<Vec<u32>>) {
+    let numbers: Vec<u32> = (0..numbers).collect();
+    let board = Just(numbers.clone())
+        .prop_shuffle()
+        .prop_map(|mut numbers| {
+            numbers.truncate(25);
+            numbers
+        });
+
+    (
+        Just(numbers).prop_shuffle(),
+        prop::collection::vec(board, boards),
+    )
+        .new_tree(&mut TestRunner::deterministic())
+        .unwrap()
+        .current()
+}
+
+pub fn play_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("play");
+    group.sample_size(10);
+
+    for (size, pool) in [(1_000, 100), (5_000, 100), (1_000, 1_000), (5_000, 1_000)] {
+        let (drawn_numbers, numbers) = generate(size, pool);
+        let parameter = format!("{}x{}", size, pool);
+
+        let boards = numbers
+            .iter()
+            .map(|numbers| {
+                let rows = numbers
+                    .chunks(5)
+                    .map(|row| row.iter().copied().map(Field::new).collect())
+                    .collect();
+                Board::new(rows).unwrap()
+            })
+            .collect();
+        let game = BingoGame::new(drawn_numbers.clone(), boards);
+
+        let scanning_boards: Vec<scanning::Board> = numbers
+            .iter()
+            .map(|numbers| scanning::Board::new(numbers))
+            .collect();
+
+        assert_eq!(
+            game.play()
+                .wins
+                .iter()
+                .map(|win| win.score)
+                .collect::<Vec<_>>(),
+            scanning::play(&drawn_numbers, scanning_boards.clone())
+        );
+
+        group.bench_with_input(BenchmarkId::new("indexed", &parameter), &game, |b, game| {
+            b.iter(|| black_box(game).play())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("scanning", &parameter),
+            &scanning_boards,
+            |b, boards| b.iter(|| scanning::play(&drawn_numbers, black_box(boards.clone()))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benchmarks, play_bench);
+criterion_main!(benchmarks);
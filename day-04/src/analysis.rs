@@ -0,0 +1,274 @@
+use crate::hash::NumberMap;
+use crate::{Board, Line, WinRules};
+use std::fmt::{self, Display, Formatter};
+
+/// When each number is first drawn
+#[derive(Debug, Clone)]
+pub struct DrawOrder {
+    numbers: Vec<u32>,
+    turns: NumberMap<usize>,
+}
+
+impl DrawOrder {
+    pub fn new(drawn_numbers: &[u32]) -> Self {
+        let mut turns = NumberMap::default();
+        for (turn, number) in drawn_numbers.iter().enumerate() {
+            turns.entry(*number).or_insert(turn);
+        }
+
+        Self {
+            numbers: drawn_numbers.to_vec(),
+            turns,
+        }
+    }
+
+    pub fn turn_of(&self, number: u32) -> Option<usize> {
+        self.turns.get(&number).copied()
+    }
+
+    /// Predicts when `board` wins without marking it
+    ///
+    /// A line is complete at the latest turn drawing one of its unmarked cells, and the board wins
+    /// with the line completing first. Lines which are marked already complete on the first draw,
+    /// like [`BingoGame::play`](crate::BingoGame::play) does. Lines holding numbers that are never
+    /// drawn don't count.
+    pub fn predict(&self, index: usize, board: &Board, rules: &WinRules) -> Option<Prediction> {
+        let (turn, line) = rules
+            .lines(board.size())
+            .into_iter()
+            .filter_map(|line| {
+                let turn = line
+                    .iter()
+                    .map(|cell| board.field(*cell))
+                    .filter(|field| !field.marked)
+                    .map(|field| self.turn_of(field.number))
+                    .try_fold(0, |latest, turn| turn.map(|turn| latest.max(turn)))?;
+
+                Some((turn, line))
+            })
+            .min_by_key(|(turn, _)| *turn)?;
+
+        let number = *self.numbers.get(turn)?;
+        let unmarked_sum: u32 = board
+            .rows()
+            .flatten()
+            .filter(|field| !field.marked)
+            .filter(|field| self.turn_of(field.number).is_none_or(|drawn| drawn > turn))
+            .map(|field| field.number)
+            .sum();
+
+        Some(Prediction {
+            board: index,
+            turn,
+            number,
+            line,
+            score: unmarked_sum * number,
+        })
+    }
+}
+
+/// The turn a board is going to win on and the line it wins with
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Prediction {
+    /// Index of the board in the order it was parsed
+    pub board: usize,
+    /// Index of the winning number among the drawn numbers
+    pub turn: usize,
+    pub number: u32,
+    /// The line completed by the winning number, the first one in rule order on ties
+    pub line: Line,
+    pub score: u32,
+}
+
+impl Display for Prediction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let cells: Vec<String> = self
+            .line
+            .iter()
+            .map(|(y, x)| format!("({}, {})", y, x))
+            .collect();
+
+        write!(
+            f,
+            "Board {} wins on draw {} when {} completes {} for a score of {}",
+            self.board,
+            self.turn + 1,
+            self.number,
+            cells.join(" "),
+            self.score
+        )
+    }
+}
+
+/// Win predictions for all boards of a game
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Analysis {
+    /// Predictions ordered by turn, boards winning on the same turn are ordered by index
+    pub ranking: Vec<Prediction>,
+    /// Boards which aren't going to win before the draws run out
+    pub never_win: Vec<usize>,
+}
+
+impl Analysis {
+    pub fn new(drawn_numbers: &[u32], boards: &[Board], rules: &WinRules) -> Self {
+        let order = DrawOrder::new(drawn_numbers);
+        let mut ranking = vec![];
+        let mut never_win = vec![];
+
+        for (i, board) in boards.iter().enumerate() {
+            match order.predict(i, board, rules) {
+                Some(prediction) => ranking.push(prediction),
+                None => never_win.push(i),
+            }
+        }
+
+        ranking.sort_by_key(|prediction| (prediction.turn, prediction.board));
+
+        Self { ranking, never_win }
+    }
+
+    pub fn prediction(&self, board: usize) -> Option<&Prediction> {
+        self.ranking
+            .iter()
+            .find(|prediction| prediction.board == board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::example;
+    use crate::{BingoGame, Field, WinCondition};
+    use proptest::prelude::*;
+
+    #[test]
+    fn explains_first_winner() {
+        let analysis = example().analyze();
+        let first = &analysis.ranking[0];
+
+        assert_eq!(first.board, 2);
+        assert_eq!(first.line, vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]);
+        assert_eq!(
+            first.to_string(),
+            "Board 2 wins on draw 12 when 24 completes (0, 0) (0, 1) (0, 2) (0, 3) (0, 4) for a \
+             score of 4512"
+        );
+    }
+
+    #[test]
+    fn ranks_boards_by_turn() {
+        let analysis = example().analyze();
+
+        assert_eq!(
+            analysis
+                .ranking
+                .iter()
+                .map(|prediction| (prediction.board, prediction.score))
+                .collect::<Vec<_>>(),
+            vec![(2, 4512), (0, 2192), (1, 1924)]
+        );
+        assert_eq!(analysis.prediction(1).unwrap().number, 13);
+        assert!(analysis.never_win.is_empty());
+    }
+
+    #[test]
+    fn ignores_numbers_drawn_again() {
+        let board = Board::new(vec![
+            vec![Field::new(1), Field::new(2)],
+            vec![Field::new(3), Field::new(4)],
+        ])
+        .unwrap();
+        let order = DrawOrder::new(&[3, 1, 3, 2]);
+        let prediction = order.predict(0, &board, &WinRules::default()).unwrap();
+
+        assert_eq!(order.turn_of(3), Some(0));
+        assert_eq!(prediction.turn, 1);
+        assert_eq!(prediction.line, vec![(0, 0), (1, 0)]);
+        assert_eq!(prediction.score, 2 + 4);
+    }
+
+    fn board(size: usize, numbers: u32) -> impl Strategy<Value = Board> {
+        prop::collection::vec(
+            prop::collection::vec((0..numbers, any::<bool>()), size),
+            size,
+        )
+        .prop_map(|rows| {
+            let rows = rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(number, marked)| Field {
+                            number,
+                            // Only premark a few fields
+                            marked: marked && number % 4 == 0,
+                        })
+                        .collect()
+                })
+                .collect();
+
+            Board::new(rows).unwrap()
+        })
+    }
+
+    fn rules() -> impl Strategy<Value = WinRules> {
+        prop::sample::subsequence(
+            vec![
+                WinCondition::Rows,
+                WinCondition::Columns,
+                WinCondition::Diagonals,
+                WinCondition::FourCorners,
+                WinCondition::Blackout,
+            ],
+            1..=5,
+        )
+        .prop_map(WinRules::new)
+    }
+
+    fn game() -> impl Strategy<Value = BingoGame> {
+        (1..=5_usize, 1..=40_u32).prop_flat_map(|(size, numbers)| {
+            (
+                prop::collection::vec(0..numbers, 0..2 * numbers as usize),
+                prop::collection::vec(board(size, numbers), 1..8),
+                rules(),
+            )
+                .prop_map(|(drawn_numbers, boards, rules)| {
+                    BingoGame::new(drawn_numbers, boards).with_rules(rules)
+                })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn matches_simulation(game in game()) {
+            let outcome = game.play();
+            let analysis = game.analyze();
+
+            prop_assert_eq!(analysis.ranking.len(), outcome.wins.len());
+            prop_assert_eq!(&analysis.never_win, &outcome.never_won);
+
+            for (prediction, win) in analysis.ranking.iter().zip(&outcome.wins) {
+                prop_assert_eq!(prediction.board, win.board);
+                prop_assert_eq!(prediction.turn, win.turn);
+                prop_assert_eq!(prediction.number, win.number);
+                prop_assert_eq!(prediction.score, win.score);
+            }
+        }
+
+        #[test]
+        fn explained_line_is_marked_at_winning_turn(game in game()) {
+            let analysis = game.analyze();
+
+            for prediction in &analysis.ranking {
+                let mut board = game.boards[prediction.board].clone();
+                for number in &game.drawn_numbers[..=prediction.turn] {
+                    board.mark_field(*number);
+                }
+
+                prop_assert!(prediction
+                    .line
+                    .iter()
+                    .all(|cell| board.field(*cell).marked));
+            }
+        }
+    }
+}
@@ -1,4 +1,9 @@
-const BOARD_SIZE: usize = 5;
+use crate::hash::NumberMap;
+use crate::rules::{Cell, Line, WinRules};
+use std::ops::Range;
+use std::sync::Arc;
+
+const DEFAULT_SIZE: usize = 5;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Field {
@@ -15,92 +20,254 @@ impl Field {
     }
 }
 
-#[derive(Debug)]
+/// The initially unmarked cells of a board, grouped by number
+#[derive(Debug, Eq, PartialEq)]
+struct NumberIndex {
+    cells: Vec<Cell>,
+    /// The range of `cells` holding the cells of a number
+    ranges: NumberMap<Range<usize>>,
+}
+
+/// The lines of a board size under some [`WinRules`], indexed by the cells they contain
+#[derive(Debug, Eq, PartialEq)]
+struct LineIndex {
+    rules: WinRules,
+    lengths: Vec<usize>,
+    by_cell: Vec<Vec<usize>>,
+}
+
+impl LineIndex {
+    fn new(rules: &WinRules, size: usize) -> Self {
+        let lines = rules.lines(size);
+        let mut by_cell = vec![vec![]; size * size];
+
+        for (i, line) in lines.iter().enumerate() {
+            for (y, x) in line {
+                by_cell[y * size + x].push(i);
+            }
+        }
+
+        Self {
+            rules: rules.clone(),
+            lengths: lines.iter().map(Vec::len).collect(),
+            by_cell,
+        }
+    }
+}
+
+/// A square bingo board
+///
+/// Keeps an index from numbers to cells and counts the marked fields of every line of its
+/// [`WinRules`], so marking a number and checking for a complete line don't have to scan the
+/// board.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Board {
-    pub rows: [[Field; BOARD_SIZE]; BOARD_SIZE],
+    size: usize,
+    fields: Vec<Field>,
+    /// Never changes after parsing, so clones of the board share it
+    numbers: Arc<NumberIndex>,
+    /// Shared between the boards of a game with the same size
+    lines: Arc<LineIndex>,
+    /// The marked fields of each line in `lines`
+    line_hits: Vec<usize>,
+    completed: bool,
+    unmarked_sum: u32,
 }
 
 impl Board {
-    pub fn try_from<I: Iterator<Item = String>>(lines: &mut I) -> Result<Self, String> {
-        let mut rows: [[Option<Field>; BOARD_SIZE]; BOARD_SIZE] = [[None; BOARD_SIZE]; BOARD_SIZE];
+    pub fn new(rows: Vec<Vec<Field>>) -> Result<Self, String> {
+        let size = rows.len();
 
-        for y in 0..BOARD_SIZE {
-            let mut columns = rows[y];
-            let line = lines.next().ok_or(format!("Expected line {}", y))?;
-            let mut line = line.split_whitespace();
+        if size == 0 {
+            return Err("Expected at least one field".to_string());
+        }
 
-            for x in 0..BOARD_SIZE {
-                let number = line.next().ok_or(format!("Expected field {}", x))?;
-                let number: u32 = number
-                    .parse()
-                    .map_err(|_| "Expected field to be a number".to_string())?;
+        if let Some(y) = rows.iter().position(|row| row.len() != size) {
+            return Err(format!("Expected {} fields in line {}", size, y));
+        }
 
-                columns[x] = Some(Field::new(number));
-            }
+        let fields: Vec<Field> = rows.into_iter().flatten().collect();
+        let mut cells: Vec<Cell> = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (y, x)))
+            .filter(|(y, x)| !fields[y * size + x].marked)
+            .collect();
+        cells.sort_by_key(|(y, x)| fields[y * size + x].number);
 
-            rows[y] = columns;
+        let mut ranges: NumberMap<Range<usize>> = NumberMap::default();
+        for (i, (y, x)) in cells.iter().enumerate() {
+            ranges
+                .entry(fields[y * size + x].number)
+                .or_insert(i..i)
+                .end = i + 1;
         }
 
-        let rows = rows.map(|row| row.map(Option::unwrap));
+        let unmarked_sum = fields
+            .iter()
+            .filter(|field| !field.marked)
+            .map(|field| field.number)
+            .sum();
+
+        let board = Self {
+            size,
+            fields,
+            numbers: Arc::new(NumberIndex { cells, ranges }),
+            lines: Arc::new(LineIndex::new(&WinRules::default(), size)),
+            line_hits: vec![],
+            completed: false,
+            unmarked_sum,
+        };
 
-        Ok(Self { rows })
+        Ok(board.count_lines())
     }
 
-    pub fn mark_field(&mut self, number: u32) {
-        for row in &mut self.rows {
-            for field in row {
-                if field.number == number {
-                    field.marked = true;
-                }
+    /// Counts the marked fields of every line from scratch
+    fn count_lines(mut self) -> Self {
+        self.line_hits = vec![0; self.lines.lengths.len()];
+        self.completed = false;
+
+        for i in 0..self.fields.len() {
+            if self.fields[i].marked {
+                self.hit(i);
             }
         }
+
+        self
     }
 
-    pub fn has_won(&self) -> bool {
-        if self.rows.iter().any(|row| row.iter().all(|field| field.marked)) {
-            return true;
+    fn hit(&mut self, i: usize) {
+        for line in &self.lines.by_cell[i] {
+            self.line_hits[*line] += 1;
+            self.completed |= self.line_hits[*line] == self.lines.lengths[*line];
         }
+    }
 
-        for i in 0..BOARD_SIZE {
-            if self.has_won_column(i) {
-                return true;
-            }
+    /// Makes [`Board::has_won`] check the lines of `rules` instead of rows and columns
+    pub fn with_rules(self, rules: &WinRules) -> Self {
+        if self.lines.rules == *rules {
+            return self;
         }
 
-        false
+        let lines = Arc::new(LineIndex::new(rules, self.size));
+        Self { lines, ..self }.count_lines()
     }
 
-    fn has_won_column(&self, column: usize) -> bool {
-        for row in &self.rows {
-            if !row[column].marked {
-                return false;
-            }
+    /// Like [`Board::with_rules`], but shares the line index with `other` if it has the same size
+    pub(crate) fn with_rules_of(self, other: &Board) -> Self {
+        if self.size != other.size {
+            return self.with_rules(other.rules());
+        }
+
+        if Arc::ptr_eq(&self.lines, &other.lines) {
+            return self;
         }
 
-        true
+        let lines = other.lines.clone();
+        Self { lines, ..self }.count_lines()
+    }
+
+    /// The rules [`Board::has_won`] checks
+    pub fn rules(&self) -> &WinRules {
+        &self.lines.rules
+    }
+
+    /// Parses a square board, inferring its size from the amount of numbers in the first line
+    pub fn try_from<I: Iterator<Item = String>>(lines: &mut I) -> Result<Self, String> {
+        let first = lines.next().ok_or("Expected line 0")?;
+        let first = parse_row(&first)?;
+        let size = first.len();
+        let mut rows = vec![first];
+
+        for y in 1..size {
+            let line = lines.next().ok_or(format!("Expected line {}", y))?;
+            rows.push(parse_row(&line)?);
+        }
+
+        Self::new(rows)
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Field]> {
+        self.fields.chunks(self.size)
+    }
+
+    pub fn field(&self, (y, x): Cell) -> Field {
+        self.fields[y * self.size + x]
+    }
+
+    /// Marks every field holding `number` and returns the cells which weren't marked before
+    pub fn mark_field(&mut self, number: u32) -> &[Cell] {
+        let size = self.size;
+        let range = match self.numbers.ranges.get(&number) {
+            Some(range) => range.clone(),
+            None => return &[],
+        };
+
+        // All cells of a number are marked together, so the first one tells whether it was drawn
+        let (y, x) = self.numbers.cells[range.start];
+        if self.fields[y * size + x].marked {
+            return &[];
+        }
+
+        for i in range.clone() {
+            let (y, x) = self.numbers.cells[i];
+            self.fields[y * size + x].marked = true;
+            self.unmarked_sum -= number;
+            self.hit(y * size + x);
+        }
+
+        &self.numbers.cells[range]
+    }
+
+    /// Whether a line of the board's [rules](Board::rules) is marked, rows and columns by default
+    pub fn has_won(&self) -> bool {
+        self.completed
+    }
+
+    pub fn has_won_with(&self, rules: &WinRules) -> bool {
+        if rules == self.rules() {
+            self.has_won()
+        } else {
+            self.winning_line(rules).is_some()
+        }
+    }
+
+    /// Returns the first line satisfying `rules` whose fields are all marked
+    pub fn winning_line(&self, rules: &WinRules) -> Option<Line> {
+        rules
+            .lines(self.size())
+            .into_iter()
+            .find(|line| line.iter().all(|cell| self.field(*cell).marked))
     }
 
     pub fn sum_of_unmarked(&self) -> u32 {
-        self.rows.iter()
-            .map(|row| row.iter().filter(|field| !field.marked).map(|field| field.number as u32).sum::<u32>())
-            .sum()
+        self.unmarked_sum
     }
 }
 
+fn parse_row(line: &str) -> Result<Vec<Field>, String> {
+    line.split_whitespace()
+        .map(|number| {
+            number
+                .parse()
+                .map(Field::new)
+                .map_err(|_| "Expected field to be a number".to_string())
+        })
+        .collect()
+}
+
 impl Default for Board {
     fn default() -> Self {
-        Self {
-            rows: [[Field {
-                number: 0,
-                marked: false,
-            }; BOARD_SIZE]; BOARD_SIZE],
-        }
+        Self::new(vec![vec![Field::new(0); DEFAULT_SIZE]; DEFAULT_SIZE]).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rules::WinCondition;
 
     const MARKED: Field = Field {
         number: 0,
@@ -133,47 +300,225 @@ mod tests {
             [6, 10, 3, 18, 5],
             [1, 12, 20, 15, 19],
         ]
-        .map(|row| row.map(Field::new));
+        .map(|row| row.map(Field::new).to_vec())
+        .to_vec();
 
-        assert_eq!(expected_rows, board.unwrap().rows);
+        assert_eq!(board.unwrap().rows().collect::<Vec<_>>(), expected_rows);
     }
 
     #[test]
     fn has_won_when_won_by_row() {
-        let board = Board {
-            rows: [
-                [MARKED, MARKED, MARKED, MARKED, MARKED],
-                [UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-            ],
-        };
+        let board = Board::new(vec![
+            vec![MARKED, MARKED, MARKED, MARKED, MARKED],
+            vec![UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![UNMARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+        ])
+        .unwrap();
 
         assert!(board.has_won());
     }
 
     #[test]
     fn has_won_when_won_by_column() {
-        let board = Board {
-            rows: [
-                [MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-                [MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
-            ],
-        };
+        let board = Board::new(vec![
+            vec![MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+            vec![MARKED, UNMARKED, UNMARKED, UNMARKED, UNMARKED],
+        ])
+        .unwrap();
 
         assert!(board.has_won());
     }
 
+    #[test]
+    fn try_from_infers_size() {
+        let mut lines = vec!["1 2 3", "4 5 6", "7 8 9", "10 11 12"]
+            .into_iter()
+            .map(ToString::to_string);
+
+        let board = Board::try_from(&mut lines).unwrap();
+        assert_eq!(board.size(), 3);
+        assert_eq!(board.field((2, 2)), Field::new(9));
+        assert_eq!(lines.next(), Some("10 11 12".to_string()));
+    }
+
+    #[test]
+    fn try_from_rejects_ragged_rows() {
+        let mut lines = vec!["1 2 3", "4 5", "7 8 9"]
+            .into_iter()
+            .map(ToString::to_string);
+
+        assert_eq!(
+            Board::try_from(&mut lines),
+            Err("Expected 3 fields in line 1".to_string())
+        );
+    }
+
+    /// A board of the given size holding the numbers `0..size²` row by row
+    fn numbered(size: usize) -> Board {
+        let rows = (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| Field::new((y * size + x) as u32))
+                    .collect()
+            })
+            .collect();
+
+        Board::new(rows).unwrap()
+    }
+
+    #[test]
+    fn not_won_by_diagonal_by_default() {
+        let mut board = numbered(5);
+        for i in 0..5 {
+            board.mark_field(i * 6);
+        }
+
+        assert!(!board.has_won());
+        assert!(board.has_won_with(&WinRules::new(vec![WinCondition::Diagonals])));
+    }
+
+    #[test]
+    fn won_by_four_corners() {
+        let mut board = numbered(5);
+        for number in [0, 4, 20, 24] {
+            board.mark_field(number);
+        }
+
+        assert!(!board.has_won());
+        assert_eq!(
+            board.winning_line(&WinRules::new(vec![WinCondition::FourCorners])),
+            Some(vec![(0, 0), (0, 4), (4, 0), (4, 4)])
+        );
+    }
+
+    #[test]
+    fn won_by_pattern() {
+        let rules = WinRules::new(vec![WinCondition::Pattern(".X./XXX/.X.".parse().unwrap())]);
+        let mut lines = vec!["1 2 3", "4 5 6", "7 8 9"]
+            .into_iter()
+            .map(ToString::to_string);
+        let mut board = Board::try_from(&mut lines).unwrap();
+
+        for number in [2, 4, 5, 6] {
+            board.mark_field(number);
+        }
+        assert!(!board.has_won_with(&rules));
+
+        board.mark_field(8);
+        assert!(board.has_won_with(&rules));
+    }
+
     #[test]
     fn mark_field() {
-        let mut board = Board::default();
-        board.rows[0][0].number = 1;
+        let mut board = numbered(5);
         board.mark_field(1);
 
-        assert!(board.rows[0][0].marked);
+        assert!(board.field((0, 1)).marked);
+    }
+
+    #[test]
+    fn mark_field_returns_newly_marked_cells() {
+        let mut board = Board::new(vec![
+            vec![Field::new(7), Field::new(1)],
+            vec![Field::new(2), Field::new(7)],
+        ])
+        .unwrap();
+
+        assert_eq!(board.mark_field(7), &[(0, 0), (1, 1)]);
+        assert_eq!(board.mark_field(7), &[]);
+        assert_eq!(board.mark_field(42), &[]);
+        assert_eq!(board.sum_of_unmarked(), 3);
+        assert!(!board.has_won());
+    }
+
+    #[test]
+    fn mark_field_skips_premarked_cells() {
+        let mut board = Board::new(vec![
+            vec![
+                Field::new(7),
+                Field {
+                    number: 7,
+                    marked: true,
+                },
+            ],
+            vec![Field::new(1), Field::new(2)],
+        ])
+        .unwrap();
+
+        assert_eq!(board.mark_field(7), &[(0, 0)]);
+        assert!(board.has_won());
+        assert_eq!(board.sum_of_unmarked(), 3);
+    }
+
+    #[test]
+    fn mark_field_marks_numbers_whose_first_cell_is_premarked() {
+        let mut board = Board::new(vec![
+            vec![
+                Field {
+                    number: 7,
+                    marked: true,
+                },
+                Field::new(1),
+            ],
+            vec![Field::new(2), Field::new(7)],
+        ])
+        .unwrap();
+
+        assert_eq!(board.mark_field(7), &[(1, 1)]);
+        assert_eq!(board.mark_field(7), &[]);
+        assert!(board.field((1, 1)).marked);
+        assert_eq!(board.sum_of_unmarked(), 3);
+    }
+
+    #[test]
+    fn counters_detect_rows_and_columns() {
+        let mut board = numbered(3);
+
+        for number in [0, 4, 8, 1] {
+            board.mark_field(number);
+        }
+        assert!(!board.has_won());
+
+        board.mark_field(7);
+        assert!(board.has_won());
+        assert_eq!(
+            board.winning_line(&WinRules::default()),
+            Some(vec![(0, 1), (1, 1), (2, 1)])
+        );
+        assert_eq!(board.sum_of_unmarked(), 2 + 3 + 5 + 6);
+    }
+
+    #[test]
+    fn counters_detect_lines_of_custom_rules() {
+        let rules = WinRules::new(vec![WinCondition::Diagonals]);
+        let mut board = numbered(3);
+        board.mark_field(0);
+        let mut board = board.with_rules(&rules);
+
+        for number in [1, 4, 5] {
+            board.mark_field(number);
+        }
+        assert!(!board.has_won());
+
+        board.mark_field(8);
+        assert!(board.has_won());
+        assert!(board.has_won_with(&rules));
+        assert!(!board.has_won_with(&WinRules::default()));
+        assert_eq!(board.rules(), &rules);
+    }
+
+    #[test]
+    fn premarked_lines_count_as_won() {
+        let board = Board::new(vec![vec![MARKED, MARKED], vec![UNMARKED, UNMARKED]]).unwrap();
+
+        assert!(board.has_won());
+        assert!(!board
+            .with_rules(&WinRules::new(vec![WinCondition::Diagonals]))
+            .has_won());
     }
 }
@@ -0,0 +1,313 @@
+use crate::hash::NumberMap;
+use crate::{parse_boards, parse_numbers, Analysis, Board, WinRules};
+use std::collections::HashMap;
+
+/// A board completing a winning line
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Win {
+    /// Index of the board in the order it was parsed
+    pub board: usize,
+    pub number: u32,
+    /// Index of the winning number among the drawn numbers
+    pub turn: usize,
+    pub score: u32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Outcome {
+    /// Wins ordered by turn, boards winning on the same turn are ordered by index
+    pub wins: Vec<Win>,
+    /// Boards which didn't win before the draws ran out
+    pub never_won: Vec<usize>,
+}
+
+impl Outcome {
+    pub fn first_winner(&self) -> Result<&Win, String> {
+        self.wins
+            .first()
+            .ok_or_else(|| "Draws ran out before any board won".to_string())
+    }
+
+    /// Returns the board which wins last, failing if any board never wins
+    pub fn last_winner(&self) -> Result<&Win, String> {
+        if !self.never_won.is_empty() {
+            return Err(format!(
+                "Draws ran out before boards {:?} won",
+                self.never_won
+            ));
+        }
+
+        self.wins
+            .last()
+            .ok_or_else(|| "Expected at least one board".to_string())
+    }
+}
+
+/// Applies `rules` to every board, boards of the same size share their line index
+fn apply_rules(boards: Vec<Board>, rules: &WinRules) -> Vec<Board> {
+    let mut by_size: HashMap<usize, Board> = HashMap::new();
+
+    boards
+        .into_iter()
+        .map(|board| {
+            let board = match by_size.get(&board.size()) {
+                Some(other) => board.with_rules_of(other),
+                None => board.with_rules(rules),
+            };
+
+            by_size.entry(board.size()).or_insert_with(|| board.clone());
+            board
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct BingoGame {
+    pub drawn_numbers: Vec<u32>,
+    pub boards: Vec<Board>,
+    pub rules: WinRules,
+}
+
+impl BingoGame {
+    pub fn new(drawn_numbers: Vec<u32>, boards: Vec<Board>) -> Self {
+        let rules = WinRules::default();
+
+        Self {
+            drawn_numbers,
+            boards: apply_rules(boards, &rules),
+            rules,
+        }
+    }
+
+    pub fn parse(mut lines: impl Iterator<Item = String>) -> Result<Self, String> {
+        let drawn_numbers = parse_numbers(&mut lines)?;
+        let boards = parse_boards(lines)?;
+
+        Ok(Self::new(drawn_numbers, boards))
+    }
+
+    /// Makes every board win with the lines of `rules`, see [`Board::with_rules`]
+    pub fn with_rules(self, rules: WinRules) -> Self {
+        Self {
+            boards: apply_rules(self.boards, &rules),
+            rules,
+            ..self
+        }
+    }
+
+    /// Plays all drawn numbers, boards stop being marked once they've won
+    ///
+    /// A board starting out with a complete line wins on the first draw.
+    pub fn play(&self) -> Outcome {
+        let mut boards = apply_rules(self.boards.clone(), &self.rules);
+        let mut won = vec![false; boards.len()];
+        let mut wins = vec![];
+
+        // The boards holding each unmarked number, so a draw only visits the boards it marks
+        let mut holders: NumberMap<Vec<usize>> = NumberMap::default();
+        for (i, board) in boards.iter().enumerate() {
+            for field in board.rows().flatten().filter(|field| !field.marked) {
+                let boards = holders.entry(field.number).or_default();
+                if boards.last() != Some(&i) {
+                    boards.push(i);
+                }
+            }
+        }
+
+        for (turn, number) in self.drawn_numbers.iter().copied().enumerate() {
+            if wins.len() == boards.len() {
+                break;
+            }
+
+            let mut candidates = holders.get(&number).cloned().unwrap_or_default();
+            if turn == 0 {
+                candidates.extend((0..boards.len()).filter(|i| boards[*i].has_won()));
+                candidates.sort_unstable();
+                candidates.dedup();
+            }
+
+            for i in candidates {
+                if won[i] {
+                    continue;
+                }
+
+                let board = &mut boards[i];
+                board.mark_field(number);
+
+                if board.has_won() {
+                    won[i] = true;
+                    wins.push(Win {
+                        board: i,
+                        number,
+                        turn,
+                        score: board.sum_of_unmarked() * number,
+                    });
+                }
+            }
+        }
+
+        Outcome {
+            wins,
+            never_won: (0..boards.len()).filter(|i| !won[*i]).collect(),
+        }
+    }
+
+    /// Predicts the outcome from the draw order alone, see [`DrawOrder::predict`]
+    ///
+    /// [`DrawOrder::predict`]: crate::DrawOrder::predict
+    pub fn analyze(&self) -> Analysis {
+        Analysis::new(&self.drawn_numbers, &self.boards, &self.rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::example;
+    use crate::{Field, WinCondition};
+
+    #[test]
+    fn play_ranks_all_wins() {
+        let outcome = example().play();
+
+        assert_eq!(
+            outcome.wins.iter().map(|win| win.board).collect::<Vec<_>>(),
+            vec![2, 0, 1]
+        );
+        assert!(outcome.never_won.is_empty());
+    }
+
+    #[test]
+    fn first_winner() {
+        let outcome = example().play();
+        let win = outcome.first_winner().unwrap();
+
+        assert_eq!(win.number, 24);
+        assert_eq!(win.turn, 11);
+        assert_eq!(win.score, 4512);
+    }
+
+    #[test]
+    fn last_winner() {
+        let outcome = example().play();
+        let win = outcome.last_winner().unwrap();
+
+        assert_eq!(win.board, 1);
+        assert_eq!(win.number, 13);
+        assert_eq!(win.score, 148 * 13);
+        assert_eq!(win.score, 1924);
+    }
+
+    #[test]
+    fn reports_boards_that_never_win() {
+        let mut game = example();
+        game.drawn_numbers.truncate(14);
+        let outcome = game.play();
+
+        assert_eq!(outcome.wins.len(), 2);
+        assert_eq!(outcome.never_won, vec![1]);
+        assert_eq!(
+            outcome.last_winner(),
+            Err("Draws ran out before boards [1] won".to_string())
+        );
+    }
+
+    #[test]
+    fn errors_when_nobody_wins() {
+        let mut game = example();
+        game.drawn_numbers.truncate(3);
+        let outcome = game.play();
+
+        assert!(outcome.first_winner().is_err());
+        assert_eq!(outcome.never_won, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn plays_with_custom_rules() {
+        let outcome = example()
+            .with_rules(WinRules::new(vec![WinCondition::Blackout]))
+            .play();
+
+        assert!(outcome.wins.iter().all(|win| win.score == 0));
+    }
+
+    #[test]
+    fn marks_repeated_numbers_in_one_turn() {
+        let board = Board::new(vec![
+            vec![Field::new(7), Field::new(1)],
+            vec![Field::new(2), Field::new(7)],
+        ])
+        .unwrap();
+        let game = BingoGame::new(vec![1, 7], vec![board])
+            .with_rules(WinRules::new(vec![WinCondition::Diagonals]));
+        let win = *game.play().first_winner().unwrap();
+
+        assert_eq!(
+            win,
+            Win {
+                board: 0,
+                number: 7,
+                turn: 1,
+                score: 2 * 7,
+            }
+        );
+    }
+
+    #[test]
+    fn premarked_lines_win_on_first_draw() {
+        let board = Board::new(vec![
+            vec![
+                Field {
+                    number: 7,
+                    marked: true,
+                },
+                Field {
+                    number: 1,
+                    marked: true,
+                },
+            ],
+            vec![Field::new(2), Field::new(3)],
+        ])
+        .unwrap();
+        let game = BingoGame::new(vec![5, 2], vec![board]);
+        let expected = Win {
+            board: 0,
+            number: 5,
+            turn: 0,
+            score: (2 + 3) * 5,
+        };
+
+        assert_eq!(game.play().wins, vec![expected]);
+        assert_eq!(game.analyze().ranking[0].score, expected.score);
+        assert!(BingoGame::new(vec![], game.boards).play().wins.is_empty());
+    }
+
+    #[test]
+    fn matches_scanning_simulation() {
+        let rules = WinRules::new(vec![WinCondition::Diagonals, WinCondition::FourCorners]);
+        let game = example().with_rules(rules.clone());
+        let mut boards = game.boards.clone();
+        let mut expected = vec![];
+
+        for (turn, number) in game.drawn_numbers.iter().copied().enumerate() {
+            for (i, board) in boards.iter_mut().enumerate() {
+                if expected.iter().any(|win: &Win| win.board == i) {
+                    continue;
+                }
+
+                board.mark_field(number);
+
+                if board.winning_line(&rules).is_some() {
+                    expected.push(Win {
+                        board: i,
+                        number,
+                        turn,
+                        score: board.sum_of_unmarked() * number,
+                    });
+                }
+            }
+        }
+
+        assert_eq!(game.play().wins, expected);
+    }
+}
@@ -0,0 +1,74 @@
+pub use crate::analysis::{Analysis, DrawOrder, Prediction};
+pub use crate::board::{Board, Field};
+pub use crate::game::{BingoGame, Outcome, Win};
+pub use crate::render::{BoardView, Style};
+pub use crate::rules::{Cell, Line, Pattern, WinCondition, WinRules};
+
+mod analysis;
+mod board;
+mod game;
+mod hash;
+mod render;
+mod rules;
+#[cfg(test)]
+mod test_util;
+
+pub fn parse_boards(lines: impl Iterator<Item = String>) -> Result<Vec<Board>, String> {
+    let mut lines = lines.filter(|line| !line.is_empty()).peekable();
+    let mut boards = vec![];
+
+    while lines.peek().is_some() {
+        let board = Board::try_from(&mut lines)?;
+        boards.push(board);
+    }
+
+    Ok(boards)
+}
+
+pub fn parse_numbers(lines: &mut impl Iterator<Item = String>) -> Result<Vec<u32>, String> {
+    lines
+        .next()
+        .ok_or("Expected drawn numbers")?
+        .split(',')
+        .map(|number| number.parse().map_err(|_| format!("Invalid drawn number {}", number)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_boards_works() {
+        let lines = vec![
+            "22 13 17 11  0",
+            " 8  2 23  4 24",
+            "21  9 14 16  7",
+            " 6 10  3 18  5",
+            " 1 12 20 15 19",
+            "",
+            "22 13 17 11  0",
+            " 8  2 23  4 24",
+            "21  9 14 16  7",
+            " 6 10  3 18  5",
+            " 1 12 20 15 19",
+        ]
+        .into_iter()
+        .map(ToString::to_string);
+
+        let boards = parse_boards(lines);
+        assert!(boards.is_ok());
+        assert_eq!(2, boards.unwrap().len());
+    }
+
+    #[test]
+    fn parse_boards_of_different_sizes() {
+        let lines = vec!["1 2", "3 4", "", "1 2 3", "4 5 6", "7 8 9"]
+            .into_iter()
+            .map(ToString::to_string);
+
+        let boards = parse_boards(lines).unwrap();
+        assert_eq!(boards[0].size(), 2);
+        assert_eq!(boards[1].size(), 3);
+    }
+}
@@ -1,181 +1,135 @@
-mod board;
-
-use crate::board::Board;
+use day_04::{BingoGame, Style};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::num::ParseIntError;
-
-fn parse_boards(lines: impl Iterator<Item = String>) -> Result<Vec<Board>, String> {
-    let mut lines = lines.filter(|line| !line.is_empty()).peekable();
-    let mut boards = vec![];
 
-    while let Some(_) = lines.peek() {
-        let board = Board::try_from(&mut lines)?;
-        boards.push(board);
-    }
+const INPUT: &str = "./input.txt";
+const USAGE: &str = "Usage: day-04 [--replay] [--board N] [--color auto|always|never]";
 
-    Ok(boards)
+struct Args {
+    replay: bool,
+    board: Option<usize>,
+    style: Style,
 }
 
-fn parse_numbers(lines: &mut impl Iterator<Item = String>) -> Result<Vec<u32>, ParseIntError> {
-    lines.next().unwrap().split(",").map(str::parse).collect()
-}
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            replay: false,
+            board: None,
+            style: Style::detect(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Expected a value for {}", arg));
+
+            match arg.as_str() {
+                "--replay" => parsed.replay = true,
+                "--board" => {
+                    let board = value()?;
+                    parsed.board = Some(
+                        board
+                            .parse()
+                            .map_err(|_| format!("Invalid board {}", board))?,
+                    );
+                }
+                "--color" => {
+                    parsed.style = match value()?.as_str() {
+                        "auto" => Style::detect(),
+                        "always" => Style::Ansi,
+                        "never" => Style::Plain,
+                        other => return Err(format!("Invalid color mode {}", other)),
+                    }
+                }
+                other => return Err(format!("Unknown argument {}", other)),
+            }
+        }
 
-#[derive(Debug)]
-struct GameResult {
-    winning_board: Board,
-    winning_number: u32,
-}
+        if parsed.board.is_some() && !parsed.replay {
+            return Err("--board requires --replay".to_string());
+        }
 
-fn get_last_winning_board(drawn_numbers: Vec<u32>, mut boards: Vec<Board>) -> GameResult {
-    let mut drawn_numbers = drawn_numbers.into_iter();
+        Ok(parsed)
+    }
+}
 
-    while let Some(number) = drawn_numbers.next() {
-        for board in &mut boards {
-            board.mark_field(number);
+/// Prints the boards after every draw until all of them have won, won boards aren't marked anymore
+fn replay(game: &BingoGame, filter: Option<usize>, style: Style) -> Result<(), String> {
+    let shown: Vec<usize> = match filter {
+        Some(board) if board >= game.boards.len() => {
+            return Err(format!("Expected board below {}", game.boards.len()))
         }
+        Some(board) => vec![board],
+        None => (0..game.boards.len()).collect(),
+    };
 
-        boards.retain(|board| !board.has_won());
+    let mut boards = game.boards.clone();
+    let mut won = vec![None; boards.len()];
 
-        if boards.len() == 1 {
+    for (turn, number) in game.drawn_numbers.iter().enumerate() {
+        if shown.iter().all(|i| won[*i].is_some()) {
             break;
         }
-    }
-
-    while let Some(number) = drawn_numbers.next() {
-        let board = &mut boards[0];
-        board.mark_field(number);
 
-        if board.has_won() {
-            let board = boards.remove(0);
+        println!("Draw {}: {}", turn + 1, number);
 
-            let result = GameResult {
-                winning_board: board,
-                winning_number: number,
-            };
+        for (i, board) in boards.iter_mut().enumerate() {
+            if won[i].is_none() {
+                board.mark_field(*number);
 
-            println!("{:?}", &result);
+                if board.has_won_with(&game.rules) {
+                    won[i] = Some(turn);
+                }
+            }
+        }
 
-            return result;
+        for i in &shown {
+            match won[*i] {
+                Some(turn) => println!(
+                    "\nBoard {} (won on draw {} with a score of {})",
+                    i,
+                    turn + 1,
+                    boards[*i].sum_of_unmarked() * game.drawn_numbers[turn]
+                ),
+                None => println!("\nBoard {}", i),
+            }
+            println!("{}", boards[*i].view(style, &game.rules));
         }
+
+        println!();
     }
 
-    unreachable!("You dun fucked up!")
+    Ok(())
 }
 
-fn get_score(mut lines: impl Iterator<Item = String>) -> u32 {
-    let drawn_numbers = parse_numbers(&mut lines).unwrap();
-    let boards = parse_boards(lines).unwrap();
-    let GameResult {
-        winning_board,
-        winning_number,
-    } = get_last_winning_board(drawn_numbers, boards);
-
-    winning_board.sum_of_unmarked() * (winning_number as u32)
+fn main() {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
 
-fn main() {
-    let lines = File::open("./input.txt")
+fn run(args: Args) -> Result<(), String> {
+    let lines = File::open(INPUT)
         .map(BufReader::new)
-        .unwrap()
+        .map_err(|e| format!("{}: {}", INPUT, e))?
         .lines()
-        .map(|line| line.unwrap());
-    let score = get_score(lines);
-
-    println!("{}", score);
-}
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", INPUT, e))?;
+    let game = BingoGame::parse(lines.into_iter())?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn parse_boards_works() {
-        let lines = vec![
-            "22 13 17 11  0",
-            " 8  2 23  4 24",
-            "21  9 14 16  7",
-            " 6 10  3 18  5",
-            " 1 12 20 15 19",
-            "",
-            "22 13 17 11  0",
-            " 8  2 23  4 24",
-            "21  9 14 16  7",
-            " 6 10  3 18  5",
-            " 1 12 20 15 19",
-        ]
-        .into_iter()
-        .map(ToString::to_string);
-
-        let boards = parse_boards(lines);
-        assert!(boards.is_ok());
-        assert_eq!(2, boards.unwrap().len());
+    if args.replay {
+        return replay(&game, args.board, args.style);
     }
 
-    #[test]
-    fn get_score_works() {
-        let lines = vec![
-            "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1",
-            "",
-            "22 13 17 11  0",
-            " 8  2 23  4 24",
-            "21  9 14 16  7",
-            " 6 10  3 18  5",
-            " 1 12 20 15 19",
-            "",
-            " 3 15  0  2 22",
-            " 9 18 13 17  5",
-            "19  8  7 25 23",
-            "20 11 10 24  4",
-            "14 21 16 12  6",
-            "",
-            "14 21 17 24  4",
-            "10 16 15  9 19",
-            "18  8 23 26 20",
-            "22 11 13  6  5",
-            " 2  0 12  3  7",
-        ]
-        .into_iter()
-        .map(ToString::to_string);
-
-        let score = get_score(lines);
-
-        assert_eq!(score, 1924);
-    }
+    let outcome = game.play();
 
-    #[test]
-    fn get_last_winning_board_works() {
-        let mut lines = vec![
-            "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1",
-            "",
-            "22 13 17 11  0",
-            " 8  2 23  4 24",
-            "21  9 14 16  7",
-            " 6 10  3 18  5",
-            " 1 12 20 15 19",
-            "",
-            " 3 15  0  2 22",
-            " 9 18 13 17  5",
-            "19  8  7 25 23",
-            "20 11 10 24  4",
-            "14 21 16 12  6",
-            "",
-            "14 21 17 24  4",
-            "10 16 15  9 19",
-            "18  8 23 26 20",
-            "22 11 13  6  5",
-            " 2  0 12  3  7",
-        ]
-            .into_iter()
-            .map(ToString::to_string);
-
-        let drawn_numbers = parse_numbers(&mut lines).unwrap();
-        let boards = parse_boards(lines).unwrap();
-
-        let GameResult { winning_number, winning_board } = get_last_winning_board(drawn_numbers, boards);
-
-        assert_eq!(winning_number, 13);
-        assert_eq!(winning_board.sum_of_unmarked(), 148);
+    println!("[Part 1] {}", outcome.first_winner()?.score);
+    println!("[Part 2] {}", outcome.last_winner()?.score);
 
-    }
+    Ok(())
 }
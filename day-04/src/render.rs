@@ -0,0 +1,156 @@
+use crate::{Board, Cell, WinRules};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, IsTerminal};
+
+const MARKED: &str = "\x1b[1;32m";
+const WINNING: &str = "\x1b[1;30;42m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Style {
+    /// Marked numbers in brackets, numbers of the winning line between asterisks
+    Plain,
+    /// Marked numbers in bold green, the winning line on a green background
+    Ansi,
+}
+
+impl Style {
+    /// Uses colors if stdout is a terminal and `NO_COLOR` isn't set
+    pub fn detect() -> Self {
+        if io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+            Style::Ansi
+        } else {
+            Style::Plain
+        }
+    }
+}
+
+/// Renders a board as an aligned grid, see [`Board::view`]
+#[derive(Debug, Clone)]
+pub struct BoardView<'a> {
+    board: &'a Board,
+    style: Style,
+    line: Vec<Cell>,
+}
+
+impl Board {
+    /// Renders the board in `style`, highlighting the line winning under `rules`
+    pub fn view(&self, style: Style, rules: &WinRules) -> BoardView<'_> {
+        BoardView {
+            board: self,
+            style,
+            line: self.winning_line(rules).unwrap_or_default(),
+        }
+    }
+}
+
+impl Display for BoardView<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let width = self
+            .board
+            .rows()
+            .flatten()
+            .map(|field| field.number.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        for (y, row) in self.board.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+
+            for (x, field) in row.iter().enumerate() {
+                if x > 0 {
+                    write!(f, " ")?;
+                }
+
+                let number = field.number;
+                let winning = self.line.contains(&(y, x));
+
+                match (self.style, winning, field.marked) {
+                    (Style::Plain, true, _) => write!(f, "*{:>w$}*", number, w = width)?,
+                    (Style::Plain, false, true) => write!(f, "[{:>w$}]", number, w = width)?,
+                    (Style::Ansi, true, _) => {
+                        write!(f, "{} {:>w$} {}", WINNING, number, RESET, w = width)?
+                    }
+                    (Style::Ansi, false, true) => {
+                        write!(f, "{} {:>w$} {}", MARKED, number, RESET, w = width)?
+                    }
+                    (_, false, false) => write!(f, " {:>w$} ", number, w = width)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the board in plain text, highlighting a full row or column
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.view(Style::Plain, &WinRules::default()).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Field, WinCondition};
+
+    fn board() -> Board {
+        let mut lines = vec!["14 21 17", "10 16 15", " 8  3  7"]
+            .into_iter()
+            .map(ToString::to_string);
+        let mut board = Board::try_from(&mut lines).unwrap();
+
+        for number in [21, 16, 8] {
+            board.mark_field(number);
+        }
+
+        board
+    }
+
+    #[test]
+    fn renders_marked_numbers() {
+        assert_eq!(
+            board().to_string(),
+            " 14  [21]  17 \n 10  [16]  15 \n[ 8]   3    7 "
+        );
+    }
+
+    #[test]
+    fn renders_winning_line() {
+        let mut board = board();
+        board.mark_field(3);
+
+        assert_eq!(
+            board.to_string(),
+            " 14  *21*  17 \n 10  *16*  15 \n[ 8] * 3*   7 "
+        );
+    }
+
+    #[test]
+    fn renders_custom_rules_with_colors() {
+        let board = Board::new(vec![
+            vec![Field::new(1), Field::new(2)],
+            vec![
+                Field {
+                    number: 3,
+                    marked: true,
+                },
+                Field::new(4),
+            ],
+        ])
+        .unwrap();
+        let rules = WinRules::new(vec![WinCondition::Pattern("../X.".parse().unwrap())]);
+
+        assert_eq!(
+            board.view(Style::Ansi, &rules).to_string(),
+            " 1   2 \n\x1b[1;30;42m 3 \x1b[0m  4 "
+        );
+        assert_eq!(
+            board.view(Style::Ansi, &WinRules::default()).to_string(),
+            " 1   2 \n\x1b[1;32m 3 \x1b[0m  4 "
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::str::FromStr;
+
+/// A cell of a board as `(row, column)`
+pub type Cell = (usize, usize);
+
+/// A set of cells which wins the game once all of them are marked
+pub type Line = Vec<Cell>;
+
+/// A custom winning pattern, parsed from rows separated by `/` or newlines
+///
+/// Marked cells are written as `X` or `#`, unmarked ones as `.`, e.g. `X...X/...../...../...../X...X`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Pattern {
+    size: usize,
+    cells: Line,
+}
+
+impl Pattern {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s
+            .split(['/', '\n'])
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let size = rows.len();
+        let mut cells = vec![];
+
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.chars().count() != size {
+                return Err(format!("Expected pattern row {} to have {} cells", y, size));
+            }
+
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    'X' | 'x' | '#' => cells.push((y, x)),
+                    '.' => {}
+                    c => return Err(format!("Invalid pattern cell {}", c)),
+                }
+            }
+        }
+
+        if cells.is_empty() {
+            return Err("Expected pattern to mark at least one cell".to_string());
+        }
+
+        Ok(Self { size, cells })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum WinCondition {
+    Rows,
+    Columns,
+    Diagonals,
+    FourCorners,
+    Blackout,
+    Pattern(Pattern),
+}
+
+impl WinCondition {
+    /// Returns every line on a board of the given size which satisfies this condition
+    pub fn lines(&self, size: usize) -> Vec<Line> {
+        if size == 0 {
+            return vec![];
+        }
+
+        match self {
+            WinCondition::Rows => (0..size)
+                .map(|y| (0..size).map(|x| (y, x)).collect())
+                .collect(),
+            WinCondition::Columns => (0..size)
+                .map(|x| (0..size).map(|y| (y, x)).collect())
+                .collect(),
+            WinCondition::Diagonals => vec![
+                (0..size).map(|i| (i, i)).collect(),
+                (0..size).map(|i| (i, size - 1 - i)).collect(),
+            ],
+            WinCondition::FourCorners => {
+                let last = size - 1;
+                let mut corners = vec![(0, 0), (0, last), (last, 0), (last, last)];
+                corners.dedup();
+
+                vec![corners]
+            }
+            WinCondition::Blackout => {
+                vec![(0..size)
+                    .flat_map(|y| (0..size).map(move |x| (y, x)))
+                    .collect()]
+            }
+            WinCondition::Pattern(pattern) if pattern.size == size => vec![pattern.cells.clone()],
+            WinCondition::Pattern(_) => vec![],
+        }
+    }
+}
+
+impl FromStr for WinCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rows" => Ok(WinCondition::Rows),
+            "columns" => Ok(WinCondition::Columns),
+            "diagonals" => Ok(WinCondition::Diagonals),
+            "corners" => Ok(WinCondition::FourCorners),
+            "blackout" => Ok(WinCondition::Blackout),
+            pattern => pattern.parse().map(WinCondition::Pattern),
+        }
+    }
+}
+
+/// The conditions under which a board wins, defaults to full rows and columns
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WinRules {
+    pub conditions: Vec<WinCondition>,
+}
+
+impl WinRules {
+    pub fn new(conditions: Vec<WinCondition>) -> Self {
+        Self { conditions }
+    }
+
+    pub fn lines(&self, size: usize) -> Vec<Line> {
+        self.conditions
+            .iter()
+            .flat_map(|condition| condition.lines(size))
+            .collect()
+    }
+}
+
+impl Default for WinRules {
+    fn default() -> Self {
+        Self::new(vec![WinCondition::Rows, WinCondition::Columns])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_lines() {
+        let lines = WinRules::default().lines(3);
+
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], vec![(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(lines[3], vec![(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn diagonal_and_corner_lines() {
+        assert_eq!(
+            WinCondition::Diagonals.lines(3),
+            vec![vec![(0, 0), (1, 1), (2, 2)], vec![(0, 2), (1, 1), (2, 0)]]
+        );
+        assert_eq!(
+            WinCondition::FourCorners.lines(5),
+            vec![vec![(0, 0), (0, 4), (4, 0), (4, 4)]]
+        );
+        assert_eq!(WinCondition::Blackout.lines(4)[0].len(), 16);
+    }
+
+    #[test]
+    fn pattern_from_str() {
+        let pattern: Pattern = "X.X/.#./...".parse().unwrap();
+
+        assert_eq!(pattern.size(), 3);
+        assert_eq!(pattern.cells(), &[(0, 0), (0, 2), (1, 1)]);
+        assert!(WinCondition::Pattern(pattern).lines(5).is_empty());
+    }
+
+    #[test]
+    fn pattern_must_be_square() {
+        assert!("X../..".parse::<Pattern>().is_err());
+        assert!(".../.../...".parse::<Pattern>().is_err());
+    }
+}
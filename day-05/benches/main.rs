@@ -0,0 +1,85 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day_05::line::{DirectionType, Line};
+use day_05::{count_dangerous_overlaps, count_dangerous_overlaps_analytic, overlap_map, Backend};
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+/// Generates horizontal, vertical and diagonal lines with coordinates in `0..range`
+fn generate(lines: usize, range: i32) -> Vec<Line> {
+    let line = (0..range, 0..range, 1..=range / 4, 0..4).prop_map(|(x, y, length, direction)| {
+        let end = match direction {
+            0 => (x + length, y),
+            1 => (x, y + length),
+            2 => (x + length, y + length),
+            _ => (x + length, y - length),
+        };
+
+        Line {
+            start: (x, y).into(),
+            end: end.into(),
+        }
+    });
+
+    prop::collection::vec(line, lines)
+        .new_tree(&mut TestRunner::deterministic())
+        .unwrap()
+        .current()
+}
+
+pub fn overlaps_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("overlaps");
+    group.sample_size(10);
+
+    for range in [1_000, 10_000, 1_000_000] {
+        let lines = generate(500, range);
+
+        if range <= 10_000 {
+            assert_eq!(
+                count_dangerous_overlaps(lines.iter().copied(), DirectionType::ALL),
+                count_dangerous_overlaps_analytic(lines.iter().copied(), DirectionType::ALL)
+            );
+
+            group.bench_with_input(BenchmarkId::new("hashmap", range), &lines, |b, lines| {
+                b.iter(|| {
+                    count_dangerous_overlaps(black_box(lines).iter().copied(), DirectionType::ALL)
+                })
+            });
+        }
+
+        group.bench_with_input(BenchmarkId::new("analytic", range), &lines, |b, lines| {
+            b.iter(|| {
+                count_dangerous_overlaps_analytic(
+                    black_box(lines).iter().copied(),
+                    DirectionType::ALL,
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+pub fn backends_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backends");
+    group.sample_size(10);
+
+    for range in [1_000, 4_000] {
+        let lines = generate(500, range);
+
+        for backend in [Backend::Dense, Backend::Sparse] {
+            let name = format!("{:?}", backend).to_lowercase();
+
+            group.bench_with_input(BenchmarkId::new(name, range), &lines, |b, lines| {
+                b.iter(|| {
+                    overlap_map(black_box(lines), DirectionType::ALL, backend).count_dangerous()
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benchmarks, overlaps_bench, backends_bench);
+criterion_main!(benchmarks);
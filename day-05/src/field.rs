@@ -0,0 +1,348 @@
+use crate::line::Line;
+use crate::point::Point;
+use crate::Bounds;
+use std::collections::HashMap;
+
+const DEFAULT_CELL_SIZE: i32 = 64;
+
+/// Lines indexed by the squares of a uniform grid they pass through
+///
+/// Queries only look at the lines of the squares they touch, and count overlaps square by square
+/// so they never hold the counts of more than one square at a time.
+#[derive(Debug, Clone)]
+pub struct VentField {
+    lines: Vec<Line>,
+    cell_size: i32,
+    /// Indices of the lines passing through each square
+    buckets: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl VentField {
+    pub fn new(lines: Vec<Line>) -> Self {
+        Self::with_cell_size(lines, DEFAULT_CELL_SIZE)
+    }
+
+    pub fn with_cell_size(lines: Vec<Line>, cell_size: i32) -> Self {
+        assert!(cell_size > 0, "Expected a positive cell size");
+
+        let mut field = Self {
+            lines: vec![],
+            cell_size,
+            buckets: HashMap::new(),
+        };
+
+        for line in lines {
+            field.insert(line);
+        }
+
+        field
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    fn cell_of(&self, point: Point) -> (i32, i32) {
+        (
+            point.x.div_euclid(self.cell_size),
+            point.y.div_euclid(self.cell_size),
+        )
+    }
+
+    /// The points of a square, clipped to the `i32` range at its ends
+    fn cell_bounds(&self, (x, y): (i32, i32)) -> Bounds {
+        let size = self.cell_size as i64;
+        let clamp = |n: i64| n.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        let (x, y) = (x as i64 * size, y as i64 * size);
+
+        Bounds {
+            min: (clamp(x), clamp(y)).into(),
+            max: (clamp(x + size - 1), clamp(y + size - 1)).into(),
+        }
+    }
+
+    /// Adds `line` to every square it passes through
+    ///
+    /// Lines at angles other than a multiple of 45° are split into their straight runs, and each
+    /// run skips to the next square right away.
+    fn insert(&mut self, line: Line) {
+        let index = self.lines.len();
+        self.lines.push(line);
+
+        let mut cells = vec![];
+
+        for run in line.runs() {
+            let mut point = run.start;
+            let step = (
+                run.end.x.cmp(&run.start.x) as i64,
+                run.end.y.cmp(&run.start.y) as i64,
+            );
+
+            loop {
+                let cell = self.cell_of(point);
+                if cells.last() != Some(&cell) {
+                    cells.push(cell);
+                }
+
+                let bounds = self.cell_bounds(cell);
+                let to_edge = |position: i32, step: i64, min: i32, max: i32| match step {
+                    1 => max as i64 - position as i64,
+                    -1 => position as i64 - min as i64,
+                    _ => i64::MAX,
+                };
+                let remaining = (run.end.x as i64 - point.x as i64)
+                    .abs()
+                    .max((run.end.y as i64 - point.y as i64).abs());
+                let steps = to_edge(point.x, step.0, bounds.min.x, bounds.max.x)
+                    .min(to_edge(point.y, step.1, bounds.min.y, bounds.max.y))
+                    .min(remaining);
+
+                if steps == remaining {
+                    break;
+                }
+
+                point = (
+                    (point.x as i64 + step.0 * (steps + 1)) as i32,
+                    (point.y as i64 + step.1 * (steps + 1)) as i32,
+                )
+                    .into();
+            }
+        }
+
+        for cell in cells {
+            self.buckets.entry(cell).or_default().push(index);
+        }
+    }
+
+    /// Indices of the lines passing through `point` in ascending order
+    pub fn lines_through(&self, point: Point) -> Vec<usize> {
+        self.buckets
+            .get(&self.cell_of(point))
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|i| self.lines[*i].contains(point))
+            .collect()
+    }
+
+    /// Calls `f` with the overlap counts of every square intersecting `area`, clipped to `area`
+    fn for_each_cell(&self, area: Bounds, mut f: impl FnMut(HashMap<Point, u32>)) {
+        let (min, max) = (self.cell_of(area.min), self.cell_of(area.max));
+        let cells_in_area = (max.0 as i64 - min.0 as i64 + 1) * (max.1 as i64 - min.1 as i64 + 1);
+
+        // Walk whichever is fewer, the squares of the area or the squares holding lines
+        let cells: Vec<(i32, i32)> = if cells_in_area <= self.buckets.len() as i64 {
+            (min.1..=max.1)
+                .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+                .filter(|cell| self.buckets.contains_key(cell))
+                .collect()
+        } else {
+            self.buckets
+                .keys()
+                .copied()
+                .filter(|(x, y)| (min.0..=max.0).contains(x) && (min.1..=max.1).contains(y))
+                .collect()
+        };
+
+        for cell in cells {
+            let bounds = self.cell_bounds(cell);
+            let clipped = Bounds {
+                min: (bounds.min.x.max(area.min.x), bounds.min.y.max(area.min.y)).into(),
+                max: (bounds.max.x.min(area.max.x), bounds.max.y.min(area.max.y)).into(),
+            };
+            let mut counts = HashMap::new();
+
+            for i in &self.buckets[&cell] {
+                for point in self.lines[*i].points_within(clipped) {
+                    *counts.entry(point).or_insert(0) += 1;
+                }
+            }
+
+            f(counts);
+        }
+    }
+
+    /// All points inside `area` with at least `k` lines and their counts, ordered by `y` and `x`
+    pub fn points_with_overlap(&self, area: Bounds, k: u32) -> Vec<(Point, u32)> {
+        let mut points = vec![];
+
+        self.for_each_cell(area, |counts| {
+            points.extend(counts.into_iter().filter(|(_, count)| *count >= k));
+        });
+
+        points.sort_by_key(|(point, _)| (point.y, point.x));
+        points
+    }
+
+    /// The point with the most lines, the first one ordered by `y` and `x` on ties
+    pub fn max_overlap(&self) -> Option<(Point, u32)> {
+        let area = Bounds::of(self.lines.iter().flat_map(|line| [line.start, line.end]))?;
+        let mut max: Option<(Point, u32)> = None;
+
+        self.for_each_cell(area, |counts| {
+            for (point, count) in counts {
+                let better = match max {
+                    None => true,
+                    Some((best, most)) => {
+                        count > most || (count == most && (point.y, point.x) < (best.y, best.x))
+                    }
+                };
+
+                if better {
+                    max = Some((point, count));
+                }
+            }
+        });
+
+        max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_lines, test_util};
+    use proptest::prelude::*;
+
+    fn example() -> VentField {
+        VentField::with_cell_size(test_util::example(), 3)
+    }
+
+    #[test]
+    fn lines_through_point() {
+        let field = example();
+
+        assert_eq!(field.lines_through((4, 4).into()), vec![1, 2, 8]);
+        assert_eq!(field.lines_through((0, 9).into()), vec![0, 6]);
+        assert!(field.lines_through((9, 9).into()).is_empty());
+    }
+
+    #[test]
+    fn points_with_overlap_in_area() {
+        let area = "0,0,9,9".parse().unwrap();
+
+        assert_eq!(example().points_with_overlap(area, 2).len(), 12);
+        assert_eq!(
+            example().points_with_overlap(area, 3),
+            vec![((4, 4).into(), 3), ((6, 4).into(), 3)]
+        );
+        assert_eq!(
+            example().points_with_overlap("0,8,3,100".parse().unwrap(), 2),
+            vec![((0, 9).into(), 2), ((1, 9).into(), 2), ((2, 9).into(), 2)]
+        );
+    }
+
+    #[test]
+    fn max_overlap() {
+        assert_eq!(example().max_overlap(), Some(((4, 4).into(), 3)));
+        assert_eq!(VentField::new(vec![]).max_overlap(), None);
+    }
+
+    #[test]
+    fn queries_long_lines_cell_by_cell() {
+        let lines = parse_lines(
+            ["0,0 -> 1000000,3", "500000,-5 -> 500000,5"]
+                .into_iter()
+                .map(ToString::to_string),
+        )
+        .collect();
+        let field = VentField::new(lines);
+
+        assert_eq!(field.max_overlap(), Some(((500_000, 2).into(), 2)));
+        assert_eq!(field.lines_through((500_000, 2).into()), vec![0, 1]);
+        assert_eq!(field.lines_through((999_999, 3).into()), vec![0]);
+    }
+
+    #[test]
+    fn squares_at_the_ends_of_the_coordinate_range() {
+        let lines = parse_lines(
+            [
+                "2147483640,0 -> 2147483647,0",
+                "2147483647,-3 -> 2147483647,3",
+                "-2147483648,5 -> -2147483640,5",
+                "-2147483644,0 -> -2147483644,10",
+                "2147483600,2147483600 -> 2147483647,2147483647",
+            ]
+            .into_iter()
+            .map(ToString::to_string),
+        )
+        .collect();
+        let field = VentField::with_cell_size(lines, 3);
+
+        assert_eq!(field.lines_through((2_147_483_647, 0).into()), vec![0, 1]);
+        assert_eq!(field.lines_through((-2_147_483_644, 5).into()), vec![2, 3]);
+        assert_eq!(field.lines_through((i32::MAX, i32::MAX).into()), vec![4]);
+        assert_eq!(
+            field.points_with_overlap("2147483600,-10,2147483647,10".parse().unwrap(), 2),
+            vec![((2_147_483_647, 0).into(), 2)]
+        );
+        assert_eq!(field.max_overlap(), Some(((2_147_483_647, 0).into(), 2)));
+    }
+
+    fn brute_force(lines: &[Line]) -> HashMap<Point, Vec<usize>> {
+        let mut points: HashMap<Point, Vec<usize>> = HashMap::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            for point in line.traverse() {
+                points.entry(point).or_default().push(i);
+            }
+        }
+
+        points
+    }
+
+    fn line() -> impl Strategy<Value = Line> {
+        (-30..30, -30..30, -30..30, -30..30, 0..3).prop_map(|(x1, y1, x2, y2, kind)| {
+            let end = match kind {
+                0 => (x2, y1),
+                1 => (x1 + (y2 - y1), y2),
+                _ => (x2, y2),
+            };
+
+            Line {
+                start: (x1, y1).into(),
+                end: end.into(),
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn queries_match_brute_force(
+            lines in prop::collection::vec(line(), 0..25),
+            cell_size in 1..12,
+            (x0, y0, x1, y1) in (-35..35_i32, -35..35_i32, -35..35_i32, -35..35_i32),
+            k in 1..4_u32,
+        ) {
+            let field = VentField::with_cell_size(lines.clone(), cell_size);
+            let points = brute_force(&lines);
+
+            for x in -32..32 {
+                for y in -32..32 {
+                    let expected = points.get(&(x, y).into()).cloned().unwrap_or_default();
+                    prop_assert_eq!(field.lines_through((x, y).into()), expected);
+                }
+            }
+
+            let area = Bounds {
+                min: (x0.min(x1), y0.min(y1)).into(),
+                max: (x0.max(x1), y0.max(y1)).into(),
+            };
+            let mut expected: Vec<(Point, u32)> = points
+                .iter()
+                .filter(|(point, lines)| area.contains(**point) && lines.len() as u32 >= k)
+                .map(|(point, lines)| (*point, lines.len() as u32))
+                .collect();
+            expected.sort_by_key(|(point, _)| (point.y, point.x));
+            prop_assert_eq!(field.points_with_overlap(area, k), expected);
+
+            let most = points.values().map(Vec::len).max();
+            let expected = points
+                .iter()
+                .filter(|(_, lines)| Some(lines.len()) == most)
+                .map(|(point, lines)| (*point, lines.len() as u32))
+                .min_by_key(|(point, _)| (point.y, point.x));
+            prop_assert_eq!(field.max_overlap(), expected);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::line::{DirectionType, Line};
+use crate::point::Point;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub mod line;
+mod field;
+mod overlap;
+pub mod point;
+mod render;
+mod segments;
+#[cfg(test)]
+mod test_util;
+
+pub use crate::field::VentField;
+pub use crate::overlap::{overlap_map, Backend, Bounds, DenseGrid, OverlapMap};
+pub use crate::render::{render, render_png, render_text, RenderFormat};
+pub use crate::segments::count_dangerous_overlaps_analytic;
+
+/// Counts how many of the lines heading in one of `directions` pass through each point
+pub fn overlap_diagram(
+    lines: impl Iterator<Item = Line>,
+    directions: &[DirectionType],
+) -> HashMap<Point, u32> {
+    let mut overlaps = HashMap::new();
+
+    for line in lines.filter(|line| directions.contains(&line.direction())) {
+        for point in line.traverse() {
+            let overlap_count = overlaps.entry(point).or_insert(0);
+            *overlap_count += 1;
+        }
+    }
+
+    overlaps
+}
+
+pub fn parse_lines(lines: impl Iterator<Item = String>) -> impl Iterator<Item = Line> {
+    lines.map(|line| Line::from_str(line.as_str()).unwrap())
+}
+
+pub fn count_dangerous_overlaps(
+    lines: impl Iterator<Item = Line>,
+    directions: &[DirectionType],
+) -> usize {
+    let overlap_diagram = overlap_diagram(lines, directions);
+
+    overlap_diagram
+        .values()
+        .filter(|overlaps| **overlaps > 1)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::example;
+
+    #[test]
+    fn overlap_diagram_works() {
+        let lines = [
+            Line {
+                start: (0, 0).into(),
+                end: (1, 0).into(),
+            },
+            Line {
+                start: (0, 0).into(),
+                end: (0, 1).into(),
+            },
+        ];
+
+        let overlap_diagram = overlap_diagram(lines.into_iter(), DirectionType::ALL);
+
+        assert_eq!(overlap_diagram.get(&(0, 0).into()), Some(&2));
+        assert_eq!(overlap_diagram.get(&(1, 0).into()), Some(&1));
+        assert_eq!(overlap_diagram.get(&(0, 1).into()), Some(&1));
+        assert_eq!(overlap_diagram.get(&(1, 1).into()), None);
+    }
+
+    #[test]
+    fn count_dangerous_overlaps_works() {
+        let overlap_counter = count_dangerous_overlaps(example().into_iter(), DirectionType::ALL);
+
+        assert_eq!(overlap_counter, 12);
+    }
+
+    #[test]
+    fn count_dangerous_overlaps_of_axis_aligned_lines() {
+        let overlap_counter =
+            count_dangerous_overlaps(example().into_iter(), DirectionType::AXIS_ALIGNED);
+
+        assert_eq!(overlap_counter, 5);
+    }
+
+    #[test]
+    fn counts_lines_at_other_angles() {
+        let lines = ["0,0 -> 4,2", "0,1 -> 4,1", "2,0 -> 2,3"];
+        let lines = || parse_lines(lines.into_iter().map(ToString::to_string));
+
+        assert_eq!(count_dangerous_overlaps(lines(), DirectionType::ALL), 2);
+        assert_eq!(
+            count_dangerous_overlaps(lines(), DirectionType::AXIS_ALIGNED),
+            1
+        );
+    }
+}
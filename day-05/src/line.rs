@@ -1,4 +1,5 @@
 use crate::point::Point;
+use crate::Bounds;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
@@ -7,33 +8,203 @@ pub enum DirectionType {
     Horizontal,
     Vertical,
     Diagonal,
+    /// Any angle other than a multiple of 45°
+    Other,
 }
 
-#[derive(Eq, PartialEq, Debug)]
+impl DirectionType {
+    pub const ALL: &'static [DirectionType] = &[
+        DirectionType::Horizontal,
+        DirectionType::Vertical,
+        DirectionType::Diagonal,
+        DirectionType::Other,
+    ];
+    pub const AXIS_ALIGNED: &'static [DirectionType] =
+        &[DirectionType::Horizontal, DirectionType::Vertical];
+}
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
 pub struct Line {
     pub start: Point,
     pub end: Point,
 }
 
 impl Line {
+    /// A line consisting of a single point counts as vertical
     pub fn direction(&self) -> DirectionType {
-        let dx = (self.start.x - self.end.x).abs();
-        let dy = (self.start.y - self.end.y).abs();
+        let (dx, dy) = self.deltas();
 
-        if dx == dy {
-            DirectionType::Diagonal
-        } else if dx == 0 {
+        if dx == 0 {
             DirectionType::Vertical
         } else if dy == 0 {
             DirectionType::Horizontal
+        } else if dx == dy {
+            DirectionType::Diagonal
         } else {
-            panic!("{} is not vertical", &self)
+            DirectionType::Other
         }
     }
 
+    /// The number of points visited by [`Line::traverse`]
+    pub fn length(&self) -> u64 {
+        let dx = (self.end.x as i64 - self.start.x as i64).unsigned_abs();
+        let dy = (self.end.y as i64 - self.start.y as i64).unsigned_abs();
+
+        dx.max(dy) + 1
+    }
+
     pub fn traverse(&self) -> Traverse {
         Traverse::new(self)
     }
+
+    /// The points of [`Line::traverse`] inside `area`, in the same order
+    ///
+    /// Lines are clipped without visiting the points outside.
+    pub fn points_within(&self, area: Bounds) -> impl Iterator<Item = Point> {
+        let line = *self;
+        let (major, minor) = line.axes();
+        let step: Point = (
+            self.end.x.cmp(&self.start.x) as i32,
+            self.end.y.cmp(&self.start.y) as i32,
+        )
+            .into();
+
+        let (x0, x1) = clip(line.start.x, step.x, area.min.x, area.max.x);
+        let (y0, y1) = clip(line.start.y, step.y, area.min.y, area.max.y);
+        // The steps along the major axis, and the range of steps along the minor one
+        let ((t0, t1), (m0, m1)) = if line.x_major() {
+            ((x0, x1), (y0, y1))
+        } else {
+            ((y0, y1), (x0, x1))
+        };
+
+        let (first, last) = if minor == 0 {
+            if m0 <= 0 && 0 <= m1 {
+                (t0, t1)
+            } else {
+                (1, 0)
+            }
+        } else {
+            // Inverts `minor_steps`, which never decreases with `t`
+            let (major, minor) = (major as i128, minor as i128);
+            let first = -(-(2 * major * m0 as i128 - major)).div_euclid(2 * minor);
+            let last = -(-(2 * major * m1 as i128 + major)).div_euclid(2 * minor) - 1;
+
+            (t0.max(first as i64), t1.min(last as i64))
+        };
+
+        (first.max(0)..last.min(major) + 1).map(move |t| line.point_at(t))
+    }
+
+    /// Splits the points of [`Line::traverse`] into maximal horizontal, vertical or diagonal runs
+    ///
+    /// Lines at other angles are split into axis-aligned or into diagonal runs, whichever are
+    /// fewer. The end of each run is found by a binary search instead of visiting its points.
+    pub fn runs(&self) -> impl Iterator<Item = Line> {
+        let line = *self;
+        let (major, minor) = line.axes();
+        // Axis-aligned steps keep `minor_steps` and diagonal ones keep `t - minor_steps`
+        let diagonal = 2 * minor > major;
+        let run = move |t: i64| {
+            if diagonal {
+                t - line.minor_steps(t)
+            } else {
+                line.minor_steps(t)
+            }
+        };
+        let mut first = 0;
+
+        std::iter::from_fn(move || {
+            if first > major {
+                return None;
+            }
+
+            let value = run(first);
+            let (mut last, mut high) = (first, major);
+            while last < high {
+                let middle = last + (high - last + 1) / 2;
+                if run(middle) == value {
+                    last = middle;
+                } else {
+                    high = middle - 1;
+                }
+            }
+
+            let start = line.point_at(first);
+            first = last + 1;
+
+            Some(Line {
+                start,
+                end: line.point_at(last),
+            })
+        })
+    }
+
+    /// Whether [`Line::traverse`] steps along `x` every time
+    fn x_major(&self) -> bool {
+        let (dx, dy) = self.deltas();
+        dx >= dy
+    }
+
+    fn deltas(&self) -> (i64, i64) {
+        (
+            (self.end.x as i64 - self.start.x as i64).abs(),
+            (self.end.y as i64 - self.start.y as i64).abs(),
+        )
+    }
+
+    /// The distances along the axis stepped along every time and along the other one
+    fn axes(&self) -> (i64, i64) {
+        let (dx, dy) = self.deltas();
+        (dx.max(dy), dx.min(dy))
+    }
+
+    /// How far [`Line::traverse`] has moved along the minor axis after `t` steps
+    fn minor_steps(&self, t: i64) -> i64 {
+        let (major, minor) = self.axes();
+
+        match major {
+            0 => 0,
+            major => ((2 * t as i128 * minor as i128 + major as i128) / (2 * major as i128)) as i64,
+        }
+    }
+
+    /// The point [`Line::traverse`] visits after `t` steps
+    fn point_at(&self, t: i64) -> Point {
+        let minor = self.minor_steps(t);
+        let (x, y) = if self.x_major() {
+            (t, minor)
+        } else {
+            (minor, t)
+        };
+
+        (
+            (self.start.x as i64 + self.end.x.cmp(&self.start.x) as i64 * x) as i32,
+            (self.start.y as i64 + self.end.y.cmp(&self.start.y) as i64 * y) as i32,
+        )
+            .into()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.points_within(Bounds {
+            min: point,
+            max: point,
+        })
+        .next()
+        .is_some()
+    }
+}
+
+/// The range of steps `t` for which `start + step * t` lies within `min..=max`
+fn clip(start: i32, step: i32, min: i32, max: i32) -> (i64, i64) {
+    let (start, min, max) = (start as i64, min as i64, max as i64);
+
+    match step {
+        0 if (min..=max).contains(&start) => (i64::MIN, i64::MAX),
+        0 => (1, 0),
+        1 => (min - start, max - start),
+        _ => (start - max, start - min),
+    }
 }
 
 impl FromStr for Line {
@@ -61,50 +232,64 @@ impl Display for Line {
     }
 }
 
-pub struct Traverse<'a> {
-    current: Point,
-    target: &'a Point,
-    direction: Point,
+/// Rasterizes a line with Bresenham's algorithm, from its start to its end point
+///
+/// Horizontal, vertical and diagonal lines step by exactly one in each direction. Every step moves
+/// by one along the longer axis, the steps along the other one are counted by `minor_steps`.
+pub struct Traverse {
+    current: Option<Point>,
+    end: Point,
+    step: Point,
+    dx: i64,
+    dy: i64,
+    error: i64,
 }
 
-impl<'a> Traverse<'a> {
-    fn new(line: &'a Line) -> Self {
-        fn normalize(n: i32) -> i32 {
-            if n.is_positive() {
-                1
-            } else if n.is_negative() {
-                -1
-            } else {
-                0
-            }
-        }
-
-        let dx = normalize(line.end.x - line.start.x);
-        let dy = normalize(line.end.y - line.start.y);
+impl Traverse {
+    fn new(line: &Line) -> Self {
+        let dx = (line.end.x as i64 - line.start.x as i64).abs();
+        let dy = -(line.end.y as i64 - line.start.y as i64).abs();
 
         Self {
-            current: line.start.clone(),
-            target: &line.end,
-            direction: (dx, dy).into(),
+            current: Some(line.start),
+            end: line.end,
+            step: (
+                line.end.x.cmp(&line.start.x) as i32,
+                line.end.y.cmp(&line.start.y) as i32,
+            )
+                .into(),
+            dx,
+            dy,
+            error: dx + dy,
         }
     }
-
-    fn target_reached(&self) -> bool {
-        self.current - self.direction == *self.target
-    }
 }
 
-impl<'a> Iterator for Traverse<'a> {
+impl Iterator for Traverse {
     type Item = Point;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.target_reached() {
-            return None;
+        let value = self.current?;
+
+        if value == self.end {
+            self.current = None;
+            return Some(value);
+        }
+
+        let mut next = value;
+        let doubled = 2 * self.error;
+
+        if doubled >= self.dy {
+            self.error += self.dy;
+            next.x += self.step.x;
         }
 
-        let value = self.current.clone();
+        if doubled <= self.dx {
+            self.error += self.dx;
+            next.y += self.step.y;
+        }
 
-        self.current += self.direction;
+        self.current = Some(next);
 
         Some(value)
     }
@@ -113,6 +298,7 @@ impl<'a> Iterator for Traverse<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn from_str_works() {
@@ -146,6 +332,18 @@ mod tests {
             end: (9, 9).into(),
         };
         assert_eq!(diagonal.direction(), DirectionType::Diagonal);
+
+        let other = Line {
+            start: (0, 0).into(),
+            end: (9, 3).into(),
+        };
+        assert_eq!(other.direction(), DirectionType::Other);
+
+        let point = Line {
+            start: (4, 2).into(),
+            end: (4, 2).into(),
+        };
+        assert_eq!(point.direction(), DirectionType::Vertical);
     }
 
     #[test]
@@ -189,4 +387,209 @@ mod tests {
         assert_eq!(trav.next(), Some((2, 2).into()));
         assert_eq!(trav.next(), None);
     }
+
+    #[test]
+    fn traverse_works_single_point() {
+        let line = Line {
+            start: (3, 3).into(),
+            end: (3, 3).into(),
+        };
+
+        assert_eq!(line.traverse().collect::<Vec<_>>(), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn traverse_works_reversed_diagonal() {
+        let line = Line {
+            start: (8, 0).into(),
+            end: (6, 2).into(),
+        };
+
+        assert_eq!(
+            line.traverse().collect::<Vec<Point>>(),
+            vec![(8, 0), (7, 1), (6, 2)]
+        );
+    }
+
+    #[test]
+    fn traverse_works_shallow() {
+        let line = Line {
+            start: (0, 0).into(),
+            end: (5, 2).into(),
+        };
+
+        assert_eq!(
+            line.traverse().collect::<Vec<Point>>(),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]
+        );
+    }
+
+    #[test]
+    fn traverse_works_steep_and_reversed() {
+        let line = Line {
+            start: (1, 3).into(),
+            end: (0, 0).into(),
+        };
+
+        assert_eq!(
+            line.traverse().collect::<Vec<Point>>(),
+            vec![(1, 3), (1, 2), (0, 1), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn points_within_clips_lines() {
+        let area = Bounds {
+            min: (2, 1).into(),
+            max: (4, 3).into(),
+        };
+
+        for (start, end) in [
+            ((0, 2), (9, 2)),
+            ((3, 9), (3, -9)),
+            ((0, -1), (9, 8)),
+            ((6, 0), (0, 6)),
+            ((0, 0), (7, 3)),
+            ((5, 0), (5, 9)),
+            ((3, 2), (3, 2)),
+        ] {
+            let line = Line {
+                start: start.into(),
+                end: end.into(),
+            };
+            let expected: Vec<Point> = line
+                .traverse()
+                .filter(|point| area.contains(*point))
+                .collect();
+
+            assert_eq!(line.points_within(area).collect::<Vec<_>>(), expected);
+            assert!(expected.iter().all(|point| line.contains(*point)));
+        }
+    }
+
+    #[test]
+    fn traverse_visits_both_end_points() {
+        for (x, y) in [(7, -3), (-4, 9), (10, 1), (-2, -11), (0, 5), (6, 0)] {
+            let line = Line {
+                start: (1, 2).into(),
+                end: (x, y).into(),
+            };
+            let points: Vec<Point> = line.traverse().collect();
+            let length = (x - 1).abs().max((y - 2).abs()) as usize + 1;
+
+            assert_eq!(points.first(), Some(&line.start));
+            assert_eq!(points.last(), Some(&line.end));
+            assert_eq!(points.len(), length);
+            assert_eq!(line.length(), length as u64);
+        }
+    }
+
+    #[test]
+    fn clips_long_lines_without_traversing_them() {
+        let line = Line {
+            start: (0, 0).into(),
+            end: (1_000_000_000, 3).into(),
+        };
+
+        // The first step along y is the one closer to y = 1 than to y = 0
+        assert!(line.contains((166_666_666, 0).into()));
+        assert!(line.contains((166_666_667, 1).into()));
+        assert!(!line.contains((166_666_667, 0).into()));
+        assert!(line.contains(line.end));
+
+        let area = Bounds {
+            min: (499_999_990, 0).into(),
+            max: (500_000_010, 5).into(),
+        };
+        let points: Vec<Point> = line.points_within(area).collect();
+        assert_eq!(points.len(), 21);
+        assert!(points
+            .iter()
+            .all(|point| point.y == 2 || point.x < 500_000_000));
+
+        let extremes = Line {
+            start: (-2_000_000_000, 1_999_999_999).into(),
+            end: (2_000_000_000, -1_999_999_998).into(),
+        };
+        assert!(extremes.contains(extremes.start));
+        assert!(extremes.contains(extremes.end));
+        let middle: Vec<Point> = extremes
+            .points_within("-2,-2,2,2".parse().unwrap())
+            .collect();
+        assert!(!middle.is_empty());
+        assert!(middle.iter().all(|point| extremes.contains(*point)));
+    }
+
+    proptest! {
+        #[test]
+        fn points_within_matches_traverse(
+            (x0, y0, x1, y1) in (-40..40_i32, -40..40_i32, -40..40_i32, -40..40_i32),
+            (ax, ay, bx, by) in (-45..45_i32, -45..45_i32, -45..45_i32, -45..45_i32),
+        ) {
+            let line = Line {
+                start: (x0, y0).into(),
+                end: (x1, y1).into(),
+            };
+            let area = Bounds {
+                min: (ax.min(bx), ay.min(by)).into(),
+                max: (ax.max(bx), ay.max(by)).into(),
+            };
+            let expected: Vec<Point> = line
+                .traverse()
+                .filter(|point| area.contains(*point))
+                .collect();
+
+            prop_assert_eq!(line.points_within(area).collect::<Vec<_>>(), expected);
+            for point in line.traverse() {
+                prop_assert!(line.contains(point));
+                prop_assert!(!line.contains((point.x, point.y + 100).into()));
+            }
+        }
+
+        #[test]
+        fn runs_partition_traverse(
+            (x0, y0, x1, y1) in (-40..40_i32, -40..40_i32, -40..40_i32, -40..40_i32),
+        ) {
+            let line = Line {
+                start: (x0, y0).into(),
+                end: (x1, y1).into(),
+            };
+            let runs: Vec<Line> = line.runs().collect();
+
+            prop_assert!(runs.iter().all(|run| run.direction() != DirectionType::Other));
+            prop_assert_eq!(
+                runs.iter().flat_map(Line::traverse).collect::<Vec<_>>(),
+                line.traverse().collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn handles_the_full_coordinate_range(
+            (x0, y0, x1, y1) in (any::<i32>(), any::<i32>(), any::<i32>(), any::<i32>()),
+        ) {
+            let line = Line {
+                start: (x0, y0).into(),
+                end: (x1, y1).into(),
+            };
+            let (dx, dy) = ((x1 as i64 - x0 as i64).abs(), (y1 as i64 - y0 as i64).abs());
+            let direction = if dx == 0 {
+                DirectionType::Vertical
+            } else if dy == 0 {
+                DirectionType::Horizontal
+            } else if dx == dy {
+                DirectionType::Diagonal
+            } else {
+                DirectionType::Other
+            };
+
+            prop_assert_eq!(line.direction(), direction);
+            prop_assert_eq!(line.length(), dx.max(dy) as u64 + 1);
+
+            let steps = (line.length() as i64).min(3);
+            prop_assert_eq!(
+                line.traverse().take(3).collect::<Vec<_>>(),
+                (0..steps).map(|t| line.point_at(t)).collect::<Vec<_>>()
+            );
+        }
+    }
 }
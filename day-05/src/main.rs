@@ -1,88 +1,104 @@
-use std::collections::HashMap;
+use day_05::line::{DirectionType, Line};
+use day_05::{overlap_map, render, Backend, Bounds, RenderFormat};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::str::FromStr;
-use crate::line::{Line};
-use crate::point::Point;
+use std::io::{self, BufRead, BufReader};
 
-pub mod point;
-pub mod line;
+const INPUT: &str = "./input.txt";
+const USAGE: &str = "Usage: day-05 [--backend auto|dense|sparse] [--render auto|text|png] [--crop x0,y0,x1,y1] [--part 1|2]";
 
-fn overlap_diagram(lines: impl Iterator<Item = Line>) -> HashMap<Point, u32> {
-    let mut overlaps = HashMap::new();
+struct Args {
+    backend: Backend,
+    /// `Some(None)` picks the format by the size of the rendered area
+    render: Option<Option<RenderFormat>>,
+    crop: Option<Bounds>,
+    part: u8,
+}
 
-    for line in lines {
-        for point in line.traverse() {
-            let overlap_count = overlaps.entry(point).or_insert(0);
-            *overlap_count += 1;
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            backend: Backend::Auto,
+            render: None,
+            crop: None,
+            part: 2,
+        };
+
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("Expected a value for {}", arg))?;
+
+            match arg.as_str() {
+                "--backend" => parsed.backend = value.parse()?,
+                "--render" => {
+                    parsed.render = match value.as_str() {
+                        "auto" => Some(None),
+                        format => Some(Some(format.parse()?)),
+                    }
+                }
+                "--crop" => parsed.crop = Some(value.parse()?),
+                "--part" => {
+                    parsed.part = match value.as_str() {
+                        "1" => 1,
+                        "2" => 2,
+                        part => return Err(format!("Invalid part {}", part)),
+                    }
+                }
+                other => return Err(format!("Unknown argument {}", other)),
+            }
         }
-    }
 
-    overlaps
-}
+        if parsed.render.is_none() && parsed.crop.is_some() {
+            return Err("--crop requires --render".to_string());
+        }
 
-fn parse_lines(lines: impl Iterator<Item = String>) -> impl Iterator<Item = Line> {
-    lines
-        .map(|line| Line::from_str(line.as_str()).unwrap())
+        Ok(parsed)
+    }
 }
 
-fn count_dangerous_overlaps(lines: impl Iterator<Item = Line>) -> usize {
-    let overlap_diagram = overlap_diagram(lines);
-
-    overlap_diagram.values()
-        .filter(|overlaps| **overlaps > 1)
-        .count()
+fn main() {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 }
 
-fn main() {
-    let lines = File::open("./input.txt")
+fn run(args: Args) -> Result<(), String> {
+    let lines = File::open(INPUT)
         .map(BufReader::new)
-        .unwrap()
+        .map_err(|e| format!("{}: {}", INPUT, e))?
         .lines()
-        .map(|line| line.unwrap());
-
-    let lines = parse_lines(lines);
-    let overlap_count = count_dangerous_overlaps(lines);
-
-    println!("{}", overlap_count);
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn overlap_diagram_works() {
-        let lines = [
-            Line { start: (0, 0).into(), end: (1, 0).into()},
-            Line { start: (0, 0).into(), end: (0, 1).into()},
-        ];
-
-        let overlap_diagram = overlap_diagram(lines.into_iter());
-
-        assert_eq!(overlap_diagram.get(&(0, 0).into()), Some(&2));
-        assert_eq!(overlap_diagram.get(&(1, 0).into()), Some(&1));
-        assert_eq!(overlap_diagram.get(&(0, 1).into()), Some(&1));
-        assert_eq!(overlap_diagram.get(&(1, 1).into()), None);
+        .enumerate()
+        .map(|(i, line)| {
+            line.map_err(|e| e.to_string())
+                .and_then(|line| line.parse())
+                .map_err(|e| format!("{} line {}: {}", INPUT, i + 1, e))
+        })
+        .collect::<Result<Vec<Line>, _>>()?;
+
+    if let Some(format) = args.render {
+        let directions = match args.part {
+            1 => DirectionType::AXIS_ALIGNED,
+            _ => DirectionType::ALL,
+        };
+        let map = overlap_map(&lines, directions, args.backend);
+        let area = match args.crop.or_else(|| map.bounds()) {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+        let format = format.unwrap_or_else(|| RenderFormat::for_area(area));
+
+        return render(format, map.as_ref(), area, &mut io::stdout().lock());
     }
 
-    #[test]
-    fn count_dangerous_overlaps_works() {
-        let lines = [
-            "0,9 -> 5,9",
-            "8,0 -> 0,8",
-            "9,4 -> 3,4",
-            "2,2 -> 2,1",
-            "7,0 -> 7,4",
-            "6,4 -> 2,0",
-            "0,9 -> 2,9",
-            "3,4 -> 1,4",
-            "0,0 -> 8,8",
-            "5,5 -> 8,2"
-        ]
-            .map(ToString::to_string);
-        let overlap_counter = count_dangerous_overlaps(parse_lines(lines.into_iter()));
+    let part_1 = overlap_map(&lines, DirectionType::AXIS_ALIGNED, args.backend);
+    let part_2 = overlap_map(&lines, DirectionType::ALL, args.backend);
 
-        assert_eq!(overlap_counter, 12);
-    }
+    println!("[Part 1] {}", part_1.count_dangerous());
+    println!("[Part 2] {}", part_2.count_dangerous());
+
+    Ok(())
 }
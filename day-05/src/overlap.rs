@@ -0,0 +1,428 @@
+use crate::line::{DirectionType, Line};
+use crate::overlap_diagram;
+use crate::point::Point;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Grids with up to this many cells per rasterized point are counted densely
+const DENSE_CELLS_PER_POINT: u64 = 16;
+
+/// Grids larger than this are never allocated, which keeps their counters within 1 GiB
+const MAX_DENSE_CELLS: u64 = 1 << 28;
+
+/// How many lines pass through each point, regardless of how they're stored
+pub trait OverlapMap {
+    /// The number of lines passing through `point`
+    fn get(&self, point: Point) -> u32;
+
+    /// The smallest box containing every point with at least one line
+    fn bounds(&self) -> Option<Bounds>;
+
+    /// Counts the points with at least `threshold` lines, `threshold` must be positive
+    fn count_at_least(&self, threshold: u32) -> usize;
+
+    /// Every point with at least one line and its count, in no particular order
+    fn points(&self) -> Box<dyn Iterator<Item = (Point, u32)> + '_>;
+
+    fn count_dangerous(&self) -> usize {
+        self.count_at_least(2)
+    }
+}
+
+impl OverlapMap for HashMap<Point, u32> {
+    fn get(&self, point: Point) -> u32 {
+        self.get(&point).copied().unwrap_or(0)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::of(self.keys().copied())
+    }
+
+    fn count_at_least(&self, threshold: u32) -> usize {
+        self.values().filter(|count| **count >= threshold).count()
+    }
+
+    fn points(&self) -> Box<dyn Iterator<Item = (Point, u32)> + '_> {
+        Box::new(self.iter().map(|(point, count)| (*point, *count)))
+    }
+}
+
+/// An inclusive rectangle of points
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn of(points: impl Iterator<Item = Point>) -> Option<Self> {
+        points.fold(None, |bounds: Option<Self>, point| {
+            Some(match bounds {
+                Some(Bounds { min, max }) => Bounds {
+                    min: (min.x.min(point.x), min.y.min(point.y)).into(),
+                    max: (max.x.max(point.x), max.y.max(point.y)).into(),
+                },
+                None => Bounds {
+                    min: point,
+                    max: point,
+                },
+            })
+        })
+    }
+
+    pub fn width(&self) -> u64 {
+        (self.max.x as i64 - self.min.x as i64 + 1) as u64
+    }
+
+    pub fn height(&self) -> u64 {
+        (self.max.y as i64 - self.min.y as i64 + 1) as u64
+    }
+
+    /// `None` if the number of points doesn't fit into `u64`
+    pub fn area(&self) -> Option<u64> {
+        self.width().checked_mul(self.height())
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+}
+
+/// Parses `x0,y0,x1,y1`, the corners may be given in any order
+impl FromStr for Bounds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR_MSG: &str = "Expected format [number],[number],[number],[number]";
+
+        let numbers = s
+            .split(',')
+            .map(|n| n.trim().parse().map_err(|_| ERROR_MSG.to_string()))
+            .collect::<Result<Vec<i32>, String>>()?;
+
+        match numbers[..] {
+            [x0, y0, x1, y1] => Ok(Bounds {
+                min: (x0.min(x1), y0.min(y1)).into(),
+                max: (x0.max(x1), y0.max(y1)).into(),
+            }),
+            _ => Err(ERROR_MSG.to_string()),
+        }
+    }
+}
+
+/// Overlap counters for every point of a bounding box
+///
+/// The counters are as wide as the ones of the sparse map, so both agree on every count.
+#[derive(Debug, Clone)]
+pub struct DenseGrid {
+    bounds: Bounds,
+    counts: Vec<u32>,
+}
+
+impl DenseGrid {
+    /// Fails for grids with more than `MAX_DENSE_CELLS` cells
+    pub fn new(bounds: Bounds) -> Result<Self, String> {
+        match bounds.area() {
+            Some(area) if area <= MAX_DENSE_CELLS => Ok(Self {
+                bounds,
+                counts: vec![0; area as usize],
+            }),
+            _ => Err(format!(
+                "A grid of {} by {} points is too large to count densely",
+                bounds.width(),
+                bounds.height()
+            )),
+        }
+    }
+
+    fn index(&self, point: Point) -> usize {
+        let x = (point.x as i64 - self.bounds.min.x as i64) as usize;
+        let y = (point.y as i64 - self.bounds.min.y as i64) as usize;
+
+        y * self.bounds.width() as usize + x
+    }
+
+    /// Counts every point of `line`, failing without counting any if it leaves the grid
+    pub fn add(&mut self, line: &Line) -> Result<(), String> {
+        // Lines never leave the box spanned by their end points
+        if !self.bounds.contains(line.start) || !self.bounds.contains(line.end) {
+            return Err(format!("Line {} leaves the grid", line));
+        }
+
+        for point in line.traverse() {
+            let i = self.index(point);
+            self.counts[i] = self.counts[i].saturating_add(1);
+        }
+
+        Ok(())
+    }
+}
+
+impl OverlapMap for DenseGrid {
+    fn get(&self, point: Point) -> u32 {
+        if self.bounds.contains(point) {
+            self.counts[self.index(point)]
+        } else {
+            0
+        }
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::of(self.points().map(|(point, _)| point))
+    }
+
+    fn count_at_least(&self, threshold: u32) -> usize {
+        self.counts
+            .iter()
+            .filter(|count| **count >= threshold)
+            .count()
+    }
+
+    fn points(&self) -> Box<dyn Iterator<Item = (Point, u32)> + '_> {
+        let width = self.bounds.width() as usize;
+
+        Box::new(
+            self.counts
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(move |(i, count)| {
+                    let x = self.bounds.min.x + (i % width) as i32;
+                    let y = self.bounds.min.y + (i / width) as i32;
+
+                    ((x, y).into(), *count)
+                }),
+        )
+    }
+}
+
+/// How an [`OverlapMap`] stores its counts
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Backend {
+    /// Dense if the bounding box isn't much larger than the number of rasterized points
+    Auto,
+    Dense,
+    Sparse,
+}
+
+impl Backend {
+    /// Picks the backend for `lines`, resolving [`Backend::Auto`] to a concrete one
+    ///
+    /// Grids too large for [`DenseGrid::new`] are counted sparsely, even if dense was asked for.
+    pub fn resolve(self, lines: &[Line]) -> Backend {
+        if self == Backend::Sparse {
+            return self;
+        }
+
+        let area = Bounds::of(lines.iter().flat_map(|line| [line.start, line.end]))
+            .and_then(|bounds| bounds.area())
+            .filter(|area| *area <= MAX_DENSE_CELLS);
+        let area = match area {
+            Some(area) => area,
+            None => return Backend::Sparse,
+        };
+        let points: u64 = lines.iter().map(|line| line.length()).sum();
+
+        if self == Backend::Dense || area <= points.saturating_mul(DENSE_CELLS_PER_POINT) {
+            Backend::Dense
+        } else {
+            Backend::Sparse
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Backend::Auto),
+            "dense" => Ok(Backend::Dense),
+            "sparse" => Ok(Backend::Sparse),
+            other => Err(format!("Invalid backend {}", other)),
+        }
+    }
+}
+
+/// Counts how many of the lines heading in one of `directions` pass through each point
+pub fn overlap_map(
+    lines: &[Line],
+    directions: &[DirectionType],
+    backend: Backend,
+) -> Box<dyn OverlapMap> {
+    let lines: Vec<Line> = lines
+        .iter()
+        .filter(|line| directions.contains(&line.direction()))
+        .copied()
+        .collect();
+
+    if backend.resolve(&lines) == Backend::Dense {
+        let bounds = Bounds::of(lines.iter().flat_map(|line| [line.start, line.end]));
+
+        if let Some(Ok(mut grid)) = bounds.map(DenseGrid::new) {
+            // The grid spans the end points of every line, so none of them leaves it
+            if lines.iter().try_for_each(|line| grid.add(line)).is_ok() {
+                return Box::new(grid);
+            }
+        }
+    }
+
+    Box::new(overlap_diagram(lines.into_iter(), DirectionType::ALL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_lines;
+    use crate::test_util::example;
+    use proptest::prelude::*;
+
+    #[test]
+    fn backends_agree_on_example() {
+        for backend in [Backend::Auto, Backend::Dense, Backend::Sparse] {
+            let map = overlap_map(&example(), DirectionType::ALL, backend);
+
+            assert_eq!(map.count_dangerous(), 12);
+            assert_eq!(map.count_at_least(3), 2);
+            assert_eq!(map.get((7, 4).into()), 2);
+            assert_eq!(map.get((-1, 4).into()), 0);
+            assert_eq!(
+                map.bounds(),
+                Some(Bounds {
+                    min: (0, 0).into(),
+                    max: (9, 9).into()
+                })
+            );
+
+            let axis_aligned = overlap_map(&example(), DirectionType::AXIS_ALIGNED, backend);
+            assert_eq!(axis_aligned.count_dangerous(), 5);
+        }
+    }
+
+    #[test]
+    fn resolves_auto_by_area() {
+        assert_eq!(Backend::Auto.resolve(&example()), Backend::Dense);
+        assert_eq!(Backend::Sparse.resolve(&example()), Backend::Sparse);
+        assert_eq!(Backend::Auto.resolve(&[]), Backend::Sparse);
+
+        let far_apart = parse_lines(
+            ["0,0 -> 10,0", "100000,100000 -> 100000,100010"]
+                .into_iter()
+                .map(ToString::to_string),
+        )
+        .collect::<Vec<_>>();
+        assert_eq!(Backend::Auto.resolve(&far_apart), Backend::Sparse);
+        assert_eq!(Backend::Dense.resolve(&far_apart), Backend::Sparse);
+        assert_eq!(Backend::Dense.resolve(&far_apart[..1]), Backend::Dense);
+    }
+
+    #[test]
+    fn large_grids_are_never_dense() {
+        let extremes = Bounds {
+            min: (i32::MIN, i32::MIN).into(),
+            max: (i32::MAX, i32::MAX).into(),
+        };
+        assert_eq!(extremes.width(), 1 << 32);
+        assert_eq!(extremes.area(), None);
+        assert!(DenseGrid::new(extremes).is_err());
+
+        let bounds = Bounds {
+            min: (0, 0).into(),
+            max: (999_999, 999_999).into(),
+        };
+        assert_eq!(bounds.area(), Some(1_000_000_000_000));
+        assert!(DenseGrid::new(bounds).is_err());
+
+        let lines = parse_lines(
+            ["0,0 -> 999999,999999", "999999,0 -> 0,999999"]
+                .into_iter()
+                .map(ToString::to_string),
+        )
+        .collect::<Vec<_>>();
+        let map = overlap_map(&lines, DirectionType::ALL, Backend::Dense);
+        assert_eq!(map.count_dangerous(), 0);
+        assert_eq!(map.get((500_000, 500_000).into()), 1);
+    }
+
+    #[test]
+    fn bounds_from_str() {
+        assert_eq!(
+            "5,-2,0,3".parse(),
+            Ok(Bounds {
+                min: (0, -2).into(),
+                max: (5, 3).into()
+            })
+        );
+        assert!("1,2,3".parse::<Bounds>().is_err());
+        assert!("1,2,3,x".parse::<Bounds>().is_err());
+    }
+
+    #[test]
+    fn dense_counts_exceed_a_byte() {
+        let line = Line {
+            start: (-3, 5).into(),
+            end: (-1, 5).into(),
+        };
+
+        for backend in [Backend::Dense, Backend::Sparse] {
+            let map = overlap_map(&[line; 300], DirectionType::ALL, backend);
+
+            assert_eq!(map.get((-2, 5).into()), 300);
+            assert_eq!(map.count_at_least(256), 3);
+            assert_eq!(map.count_at_least(301), 0);
+        }
+    }
+
+    #[test]
+    fn dense_grid_rejects_lines_outside() {
+        let mut grid = DenseGrid::new("0,0,4,4".parse().unwrap()).unwrap();
+        let inside: Line = "0,0 -> 4,4".parse().unwrap();
+        let outside: Line = "0,2 -> 5,2".parse().unwrap();
+
+        assert_eq!(grid.add(&inside), Ok(()));
+        assert!(grid.add(&outside).is_err());
+        assert_eq!(grid.get((2, 2).into()), 1);
+        assert_eq!(grid.count_at_least(1), 5);
+    }
+
+    #[test]
+    fn empty_maps() {
+        for backend in [Backend::Dense, Backend::Sparse] {
+            let map = overlap_map(&[], DirectionType::ALL, backend);
+
+            assert_eq!(map.bounds(), None);
+            assert_eq!(map.count_dangerous(), 0);
+        }
+    }
+
+    fn line() -> impl Strategy<Value = Line> {
+        (-20..20, -20..20, -20..20, -20..20).prop_map(|(x1, y1, x2, y2)| Line {
+            start: (x1, y1).into(),
+            end: (x2, y2).into(),
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn dense_matches_sparse(lines in prop::collection::vec(line(), 0..30)) {
+            let dense = overlap_map(&lines, DirectionType::ALL, Backend::Dense);
+            let sparse = overlap_map(&lines, DirectionType::ALL, Backend::Sparse);
+
+            prop_assert_eq!(dense.bounds(), sparse.bounds());
+
+            let mut dense_points: Vec<_> = dense.points().map(|(p, n)| (p.x, p.y, n)).collect();
+            let mut sparse_points: Vec<_> = sparse.points().map(|(p, n)| (p.x, p.y, n)).collect();
+            dense_points.sort_unstable();
+            sparse_points.sort_unstable();
+            prop_assert_eq!(dense_points, sparse_points);
+            for threshold in 1..4 {
+                prop_assert_eq!(dense.count_at_least(threshold), sparse.count_at_least(threshold));
+            }
+            for x in -20..20 {
+                for y in -20..20 {
+                    prop_assert_eq!(dense.get((x, y).into()), sparse.get((x, y).into()));
+                }
+            }
+        }
+    }
+}
@@ -45,7 +45,7 @@ impl PartialEq<(i32, i32)> for Point {
 
 impl From<(i32, i32)> for Point {
     fn from((x, y): (i32, i32)) -> Self {
-        Self { x, y, }
+        Self { x, y }
     }
 }
 
@@ -56,7 +56,8 @@ impl FromStr for Point {
         const ERROR_MSG: &str = "Expected format [number],[number]";
 
         fn parse(s: Option<&str>) -> Result<i32, String> {
-            s.ok_or(ERROR_MSG.to_string()).and_then(|s| s.parse().map_err(|_| ERROR_MSG.to_string()))
+            s.ok_or(ERROR_MSG.to_string())
+                .and_then(|s| s.parse().map_err(|_| ERROR_MSG.to_string()))
         }
 
         let mut split = s.split(",");
@@ -82,6 +83,6 @@ mod tests {
     fn from_str_works() {
         let str = "1,2";
 
-        assert_eq!(Point::from_str(str), Ok(Point { x: 1, y: 2}))
+        assert_eq!(Point::from_str(str), Ok(Point { x: 1, y: 2 }))
     }
-}
\ No newline at end of file
+}
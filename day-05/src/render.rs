@@ -0,0 +1,192 @@
+use crate::{Bounds, OverlapMap};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Areas up to this many points wide and high are rendered as text by default
+const MAX_TEXT_SIZE: u64 = 120;
+
+/// Larger areas are scaled down to fit into this many pixels in both dimensions
+const MAX_IMAGE_SIZE: u64 = 2048;
+
+/// The darkest shade of points with at least one line, so single lines stay visible
+const MIN_SHADE: u32 = 64;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RenderFormat {
+    /// The puzzle's diagram, `.` for no line and the number of lines otherwise
+    Text,
+    /// A grayscale heatmap, brighter pixels have more lines
+    Png,
+}
+
+impl RenderFormat {
+    /// Text for areas which fit into a terminal, PNG otherwise
+    pub fn for_area(area: Bounds) -> Self {
+        if area.width() <= MAX_TEXT_SIZE && area.height() <= MAX_TEXT_SIZE {
+            RenderFormat::Text
+        } else {
+            RenderFormat::Png
+        }
+    }
+}
+
+impl FromStr for RenderFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(RenderFormat::Text),
+            "png" => Ok(RenderFormat::Png),
+            format => Err(format!("Invalid render format {}", format)),
+        }
+    }
+}
+
+pub fn render(
+    format: RenderFormat,
+    map: &dyn OverlapMap,
+    area: Bounds,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    match format {
+        RenderFormat::Text => render_text(map, area, out).map_err(|e| e.to_string()),
+        RenderFormat::Png => render_png(map, area, out),
+    }
+}
+
+/// Writes one row per `y`, counts above 9 are written as `#`
+pub fn render_text(map: &dyn OverlapMap, area: Bounds, out: &mut impl Write) -> io::Result<()> {
+    for y in area.min.y..=area.max.y {
+        let row: String = (area.min.x..=area.max.x)
+            .map(|x| match map.get((x, y).into()) {
+                0 => '.',
+                count @ 1..=9 => char::from_digit(count, 10).unwrap(),
+                _ => '#',
+            })
+            .collect();
+
+        writeln!(out, "{}", row)?;
+    }
+
+    Ok(())
+}
+
+/// Writes an 8-bit grayscale PNG with one pixel per point, or per square of points if `area` is
+/// too large, showing the highest count of the square
+pub fn render_png(map: &dyn OverlapMap, area: Bounds, out: &mut impl Write) -> Result<(), String> {
+    let scale = area.width().max(area.height()).div_ceil(MAX_IMAGE_SIZE);
+    let width = area.width().div_ceil(scale);
+    let height = area.height().div_ceil(scale);
+
+    let mut counts = vec![0; (width * height) as usize];
+    for (point, count) in map.points() {
+        if area.contains(point) {
+            let x = (point.x as i64 - area.min.x as i64) as u64 / scale;
+            let y = (point.y as i64 - area.min.y as i64) as u64 / scale;
+            let i = (y * width + x) as usize;
+
+            counts[i] = counts[i].max(count);
+        }
+    }
+
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let pixels: Vec<u8> = counts
+        .into_iter()
+        .map(|count| match count {
+            0 => 0,
+            _ => (MIN_SHADE + (count - 1) * (255 - MIN_SHADE) / (max - 1).max(1)) as u8,
+        })
+        .collect();
+
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| format!("Failed to write PNG: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line::{DirectionType, Line};
+    use crate::test_util::example;
+    use crate::{overlap_map, Backend};
+
+    fn text(directions: &[DirectionType], area: Option<Bounds>) -> String {
+        let map = overlap_map(&example(), directions, Backend::Auto);
+        let mut out = vec![];
+        render_text(map.as_ref(), area.or(map.bounds()).unwrap(), &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn renders_part_1_diagram() {
+        assert_eq!(
+            text(DirectionType::AXIS_ALIGNED, None),
+            include_str!("../golden/example-part-1.txt")
+        );
+    }
+
+    #[test]
+    fn renders_part_2_diagram() {
+        assert_eq!(
+            text(DirectionType::ALL, None),
+            include_str!("../golden/example-part-2.txt")
+        );
+    }
+
+    #[test]
+    fn renders_cropped_area() {
+        let area = "3,3,6,5".parse().unwrap();
+
+        assert_eq!(text(DirectionType::ALL, Some(area)), "1.2.\n2313\n1.2.\n");
+    }
+
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        let decoder = png::Decoder::new(png);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        pixels.truncate(info.buffer_size());
+
+        (info.width, info.height, pixels)
+    }
+
+    #[test]
+    fn renders_png_heatmap() {
+        let map = overlap_map(&example(), DirectionType::ALL, Backend::Sparse);
+        let mut out = vec![];
+        render_png(map.as_ref(), "3,3,6,5".parse().unwrap(), &mut out).unwrap();
+
+        let (width, height, pixels) = decode(&out);
+        assert_eq!((width, height), (4, 3));
+        assert_eq!(
+            pixels,
+            vec![64, 0, 159, 0, 159, 255, 64, 255, 64, 0, 159, 0]
+        );
+    }
+
+    #[test]
+    fn scales_down_large_areas() {
+        let lines = [Line {
+            start: (0, 0).into(),
+            end: (9999, 0).into(),
+        }];
+        let map = overlap_map(&lines, DirectionType::ALL, Backend::Sparse);
+        let area = map.bounds().unwrap();
+        let mut out = vec![];
+
+        assert_eq!(RenderFormat::for_area(area), RenderFormat::Png);
+        render(RenderFormat::Png, map.as_ref(), area, &mut out).unwrap();
+
+        let (width, height, pixels) = decode(&out);
+        assert_eq!((width, height), (2000, 1));
+        assert!(pixels.iter().all(|pixel| *pixel == 64));
+    }
+}
@@ -0,0 +1,396 @@
+use crate::line::{DirectionType, Line};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The four directions which lines can be merged along, as `a * x + b * y = key`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Family {
+    Horizontal,
+    Vertical,
+    /// `y - x` is constant
+    Rising,
+    /// `y + x` is constant
+    Falling,
+}
+
+impl Family {
+    const ALL: [Family; 4] = [
+        Family::Horizontal,
+        Family::Vertical,
+        Family::Rising,
+        Family::Falling,
+    ];
+
+    fn coefficients(self) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Rising => (-1, 1),
+            Family::Falling => (1, 1),
+        }
+    }
+
+    fn key(self, (x, y): (i64, i64)) -> i64 {
+        let (a, b) = self.coefficients();
+
+        a * x + b * y
+    }
+
+    /// The position of a point along a line of this family
+    fn position(self, (x, y): (i64, i64)) -> i64 {
+        match self {
+            Family::Vertical => y,
+            _ => x,
+        }
+    }
+
+    /// The point at `position` along the line of this family with `key`
+    fn point(self, key: i64, position: i64) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (position, key),
+            Family::Vertical => (key, position),
+            Family::Rising => (position, key + position),
+            Family::Falling => (position, key - position),
+        }
+    }
+
+    /// The range of keys of `other` which the interval of this family passes through
+    fn span(self, key: i64, (start, end): (i64, i64), other: Family) -> (i64, i64) {
+        let start = other.key(self.point(key, start));
+        let end = other.key(self.point(key, end));
+
+        (start.min(end), start.max(end))
+    }
+
+    /// Solves both line equations, returning the intersection if it's a lattice point
+    fn intersection(self, key: i64, other: Family, other_key: i64) -> Option<(i64, i64)> {
+        let (a1, b1) = self.coefficients();
+        let (a2, b2) = other.coefficients();
+        let determinant = a1 * b2 - a2 * b1;
+
+        if determinant == 0 {
+            return None;
+        }
+
+        let x = key * b2 - other_key * b1;
+        let y = a1 * other_key - a2 * key;
+
+        if x % determinant != 0 || y % determinant != 0 {
+            return None;
+        }
+
+        Some((x / determinant, y / determinant))
+    }
+}
+
+/// Closed intervals of positions along the lines of one family, grouped by key
+#[derive(Debug, Default)]
+struct Intervals(HashMap<i64, Vec<(i64, i64)>>);
+
+impl Intervals {
+    fn contains(&self, family: Family, point: (i64, i64)) -> bool {
+        let intervals = match self.0.get(&family.key(point)) {
+            Some(intervals) => intervals,
+            None => return false,
+        };
+        let position = family.position(point);
+        let i = intervals.partition_point(|(_, end)| *end < position);
+
+        intervals
+            .get(i)
+            .is_some_and(|(start, _)| *start <= position)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (i64, (i64, i64))> + '_ {
+        self.0
+            .iter()
+            .flat_map(|(key, intervals)| intervals.iter().map(move |interval| (*key, *interval)))
+    }
+
+    fn points(&self) -> u64 {
+        self.iter()
+            .map(|(_, (start, end))| (end - start + 1) as u64)
+            .sum()
+    }
+}
+
+/// The segments of one family merged into the positions covered at least once and at least twice
+#[derive(Debug, Default)]
+struct Coverage {
+    union: Intervals,
+    overlaps: Intervals,
+}
+
+impl Coverage {
+    fn new(segments: HashMap<i64, Vec<(i64, i64)>>) -> Self {
+        let mut coverage = Self::default();
+
+        for (key, segments) in segments {
+            let mut events: Vec<(i64, i32)> = segments
+                .iter()
+                .flat_map(|(start, end)| [(*start, 1), (end + 1, -1)])
+                .collect();
+            events.sort_unstable();
+
+            let mut union = vec![];
+            let mut overlaps = vec![];
+            let mut covered = 0;
+
+            for (position, change) in events {
+                let before = covered;
+                covered += change;
+
+                for (intervals, threshold) in [(&mut union, 1), (&mut overlaps, 2)] {
+                    if before < threshold && covered >= threshold {
+                        extend(intervals, position);
+                    } else if before >= threshold && covered < threshold {
+                        let last: &mut (i64, i64) = intervals.last_mut().unwrap();
+                        last.1 = position - 1;
+                    }
+                }
+            }
+
+            coverage.union.0.insert(key, union);
+            if !overlaps.is_empty() {
+                coverage.overlaps.0.insert(key, overlaps);
+            }
+        }
+
+        coverage
+    }
+}
+
+/// Opens an interval at `position`, continuing the last one if it ends right before
+fn extend(intervals: &mut Vec<(i64, i64)>, position: i64) {
+    match intervals.last_mut() {
+        Some(last) if last.1 == position - 1 => last.1 = i64::MAX,
+        _ => intervals.push((position, i64::MAX)),
+    }
+}
+
+fn insert(
+    segments: &mut [HashMap<i64, Vec<(i64, i64)>>],
+    family: Family,
+    start: (i64, i64),
+    end: (i64, i64),
+) {
+    let (a, b) = (family.position(start), family.position(end));
+
+    segments[family as usize]
+        .entry(family.key(start))
+        .or_default()
+        .push((a.min(b), a.max(b)));
+}
+
+/// Adds the lattice points where the merged lines of `family` cross those of `other`
+///
+/// Along the keys of `other` the lines of `family` span ranges at a fixed key of `family`, while
+/// those of `other` sit at a single point, so sweeping over the keys of `other` with the active
+/// keys of `family` in a sorted set only visits lines which actually cross.
+fn add_crossings(
+    (family, coverage): (Family, &Coverage),
+    (other, other_coverage): (Family, &Coverage),
+    crossings: &mut HashSet<(i64, i64)>,
+) {
+    // Ordered by the key of `other`, lines of `family` start before and end after the queries
+    const START: u8 = 0;
+    const QUERY: u8 = 1;
+    const END: u8 = 2;
+
+    let mut events = vec![];
+
+    for (key, interval) in coverage.union.iter() {
+        let (start, end) = family.span(key, interval, other);
+        events.push((start, START, key, key));
+        events.push((end, END, key, key));
+    }
+
+    for (other_key, interval) in other_coverage.union.iter() {
+        let (start, end) = other.span(other_key, interval, family);
+        events.push((other_key, QUERY, start, end));
+    }
+
+    events.sort_unstable();
+
+    let mut active = BTreeSet::new();
+    for (other_key, kind, start, end) in events {
+        match kind {
+            START => {
+                active.insert(start);
+            }
+            END => {
+                active.remove(&start);
+            }
+            _ => crossings.extend(
+                active
+                    .range(start..=end)
+                    .filter_map(|key| family.intersection(*key, other, other_key)),
+            ),
+        }
+    }
+}
+
+/// Counts the points covered by at least two lines without visiting every point of the lines
+///
+/// Lines at angles other than a multiple of 45° are split into their horizontal, vertical or
+/// diagonal runs first. The runs are merged per family by sweeping over their start and end
+/// points, which yields the points covered twice by one family as intervals. Points covered by
+/// lines of different families are found by sweeping over the merged lines of each pair of
+/// families.
+///
+/// Agrees with [`count_dangerous_overlaps`](crate::count_dangerous_overlaps).
+pub fn count_dangerous_overlaps_analytic(
+    lines: impl Iterator<Item = Line>,
+    directions: &[DirectionType],
+) -> usize {
+    let mut segments: Vec<HashMap<i64, Vec<(i64, i64)>>> = vec![HashMap::new(); 4];
+
+    for line in lines.filter(|line| directions.contains(&line.direction())) {
+        for run in line.runs() {
+            let start = (run.start.x as i64, run.start.y as i64);
+            let end = (run.end.x as i64, run.end.y as i64);
+
+            let family = match run.direction() {
+                DirectionType::Horizontal => Family::Horizontal,
+                DirectionType::Vertical => Family::Vertical,
+                _ if (end.0 - start.0) * (end.1 - start.1) > 0 => Family::Rising,
+                _ => Family::Falling,
+            };
+
+            insert(&mut segments, family, start, end);
+        }
+    }
+
+    let coverages: Vec<Coverage> = segments.into_iter().map(Coverage::new).collect();
+
+    let mut crossings = HashSet::new();
+    for (i, family) in Family::ALL.into_iter().enumerate() {
+        for (j, other) in Family::ALL.into_iter().enumerate().skip(i + 1) {
+            add_crossings(
+                (family, &coverages[i]),
+                (other, &coverages[j]),
+                &mut crossings,
+            );
+        }
+    }
+
+    // Crossings lying in intervals covered twice by some families were counted once per family
+    let mut count: i64 = coverages
+        .iter()
+        .map(|coverage| coverage.overlaps.points() as i64)
+        .sum();
+
+    for point in crossings {
+        let counted = Family::ALL
+            .into_iter()
+            .zip(&coverages)
+            .filter(|(family, coverage)| coverage.overlaps.contains(*family, point))
+            .count() as i64;
+
+        count += 1 - counted;
+    }
+
+    count as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::example;
+    use crate::{count_dangerous_overlaps, parse_lines};
+    use proptest::prelude::*;
+
+    fn lines(lines: &[&str]) -> Vec<Line> {
+        parse_lines(lines.iter().map(ToString::to_string)).collect()
+    }
+
+    #[test]
+    fn counts_example() {
+        let example = example();
+
+        assert_eq!(
+            count_dangerous_overlaps_analytic(example.iter().copied(), DirectionType::ALL),
+            12
+        );
+        assert_eq!(
+            count_dangerous_overlaps_analytic(example.into_iter(), DirectionType::AXIS_ALIGNED),
+            5
+        );
+    }
+
+    #[test]
+    fn counts_long_lines() {
+        let lines = lines(&[
+            "0,0 -> 3000000,0",
+            "1000000,0 -> 5000000,0",
+            "2000000,-7 -> 2000000,7",
+            "0,0 -> 3000000,3000000",
+            "10,10 -> 10,10",
+        ]);
+
+        // The shared part of the horizontal lines, the start of the diagonal and the point on it
+        assert_eq!(
+            count_dangerous_overlaps_analytic(lines.into_iter(), DirectionType::ALL),
+            2000001 + 2
+        );
+    }
+
+    #[test]
+    fn counts_long_lines_at_other_angles() {
+        let lines = lines(&[
+            "0,0 -> 2000000000,0",
+            "0,0 -> 1000000000,3",
+            "500000000,-5 -> 500000000,5",
+            "-3,-1000000000 -> 3,1000000000",
+        ]);
+
+        // The first run of the shallow line, which also holds the point of the steep line at y = 0,
+        // and the vertical line crossing the horizontal and the shallow line
+        assert_eq!(
+            count_dangerous_overlaps_analytic(lines.into_iter(), DirectionType::ALL),
+            166666667 + 2
+        );
+    }
+
+    #[test]
+    fn diagonals_cross_between_lattice_points() {
+        let lines = lines(&["0,0 -> 3,3", "0,3 -> 3,0", "0,1 -> 2,3", "0,2 -> 2,0"]);
+
+        assert_eq!(
+            count_dangerous_overlaps_analytic(lines.iter().copied(), DirectionType::ALL),
+            count_dangerous_overlaps(lines.into_iter(), DirectionType::ALL)
+        );
+    }
+
+    fn line(range: i32) -> impl Strategy<Value = Line> {
+        let coordinate = move || -range..=range;
+
+        prop_oneof![
+            (coordinate(), coordinate(), coordinate(), coordinate())
+                .prop_map(|(x1, y1, x2, y2)| ((x1, y1), (x2, y2))),
+            (coordinate(), coordinate(), coordinate()).prop_map(|(x1, x2, y)| ((x1, y), (x2, y))),
+            (coordinate(), coordinate(), coordinate()).prop_map(|(x, y1, y2)| ((x, y1), (x, y2))),
+            (coordinate(), coordinate(), -range..=range, any::<bool>()).prop_map(
+                |(x, y, length, rising)| {
+                    let dy = if rising { length } else { -length };
+                    ((x, y), (x + length, y + dy))
+                }
+            ),
+        ]
+        .prop_map(|(start, end)| Line {
+            start: start.into(),
+            end: end.into(),
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn matches_rasterized_count(lines in prop::collection::vec(line(12), 0..40)) {
+            for directions in [DirectionType::ALL, DirectionType::AXIS_ALIGNED] {
+                prop_assert_eq!(
+                    count_dangerous_overlaps_analytic(lines.iter().copied(), directions),
+                    count_dangerous_overlaps(lines.iter().copied(), directions)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::line::Line;
+use crate::parse_lines;
+
+/// The vents from the puzzle description
+pub fn example() -> Vec<Line> {
+    let lines = [
+        "0,9 -> 5,9",
+        "8,0 -> 0,8",
+        "9,4 -> 3,4",
+        "2,2 -> 2,1",
+        "7,0 -> 7,4",
+        "6,4 -> 2,0",
+        "0,9 -> 2,9",
+        "3,4 -> 1,4",
+        "0,0 -> 8,8",
+        "5,5 -> 8,2",
+    ];
+
+    parse_lines(lines.into_iter().map(ToString::to_string)).collect()
+}
@@ -0,0 +1,316 @@
+use crate::matrix::{Checked, Matrix};
+use crate::PopulationModel;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The population on one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub day: usize,
+    pub total: u128,
+    /// The number of fish per timer, regardless of how long they've lived
+    pub by_age: Vec<u128>,
+    /// The population divided by the one of the day before, `None` on day 0 or after extinction
+    pub growth: Option<f64>,
+    /// How far `growth` is off from the growth in the long run
+    pub eigenvalue_gap: Option<f64>,
+}
+
+/// Iterates over the daily snapshots of a population until it no longer fits into `u128`
+#[derive(Debug, Clone)]
+pub struct Snapshots {
+    transition: Matrix<u128>,
+    timers: usize,
+    eigenvalue: f64,
+    day: usize,
+    by_age: Option<Vec<u128>>,
+    previous_total: Option<u128>,
+    overflowed_on: Option<usize>,
+}
+
+impl Snapshots {
+    pub fn new(model: &PopulationModel, by_age: &[usize]) -> Self {
+        Self {
+            // The entries are at most `offspring + 1`, which always fits into `u128`
+            transition: model.transition_matrix(&Checked).unwrap(),
+            timers: model.timers(),
+            eigenvalue: model.dominant_eigenvalue(),
+            day: 0,
+            by_age: Some(by_age.iter().map(|n| *n as u128).collect()),
+            previous_total: None,
+            overflowed_on: None,
+        }
+    }
+
+    /// The day the population stopped fitting into `u128`, once the snapshots ended there
+    pub fn overflowed_on(&self) -> Option<usize> {
+        self.overflowed_on
+    }
+
+    /// The factor the population grows by each day in the long run
+    pub fn eigenvalue(&self) -> f64 {
+        self.eigenvalue
+    }
+}
+
+impl Iterator for Snapshots {
+    type Item = Snapshot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let by_age = self.by_age.take()?;
+        let total = match by_age.iter().try_fold(0_u128, |sum, n| sum.checked_add(*n)) {
+            Some(total) => total,
+            None => {
+                self.overflowed_on = Some(self.day);
+                return None;
+            }
+        };
+
+        let mut by_timer = vec![0; self.timers];
+        for (i, count) in by_age.iter().enumerate() {
+            by_timer[i % self.timers] += count;
+        }
+
+        let growth = self
+            .previous_total
+            .filter(|previous| *previous > 0)
+            .map(|previous| total as f64 / previous as f64);
+        let snapshot = Snapshot {
+            day: self.day,
+            total,
+            by_age: by_timer,
+            growth,
+            eigenvalue_gap: growth.map(|growth| growth - self.eigenvalue),
+        };
+
+        self.by_age = self.transition.apply(&by_age, &Checked);
+        self.previous_total = Some(total);
+        self.day += 1;
+
+        if self.by_age.is_none() {
+            self.overflowed_on = Some(self.day);
+        }
+
+        Some(snapshot)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SeriesFormat {
+    Csv,
+    /// An array with one object per day
+    Json,
+}
+
+impl FromStr for SeriesFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(SeriesFormat::Csv),
+            "json" => Ok(SeriesFormat::Json),
+            format => Err(format!("Invalid series format {}", format)),
+        }
+    }
+}
+
+pub fn write_series(
+    format: SeriesFormat,
+    snapshots: impl Iterator<Item = Snapshot>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    match format {
+        SeriesFormat::Csv => write_csv(snapshots, out),
+        SeriesFormat::Json => write_json(snapshots, out),
+    }
+}
+
+fn optional(value: Option<f64>, none: &str) -> String {
+    value.map_or(none.to_string(), |value| value.to_string())
+}
+
+/// Writes a header and one row per day, with one column per timer and empty cells for `None`
+pub fn write_csv(
+    snapshots: impl Iterator<Item = Snapshot>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut snapshots = snapshots.peekable();
+    let timers = snapshots.peek().map_or(0, |snapshot| snapshot.by_age.len());
+    let ages: String = (0..timers).map(|age| format!(",age_{}", age)).collect();
+
+    writeln!(out, "day,total,growth,eigenvalue_gap{}", ages)?;
+
+    for snapshot in snapshots {
+        let by_age: String = snapshot
+            .by_age
+            .iter()
+            .map(|count| format!(",{}", count))
+            .collect();
+
+        writeln!(
+            out,
+            "{},{},{},{}{}",
+            snapshot.day,
+            snapshot.total,
+            optional(snapshot.growth, ""),
+            optional(snapshot.eigenvalue_gap, ""),
+            by_age
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn write_json(
+    snapshots: impl Iterator<Item = Snapshot>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    write!(out, "[")?;
+
+    for (i, snapshot) in snapshots.enumerate() {
+        let by_age: Vec<String> = snapshot.by_age.iter().map(u128::to_string).collect();
+
+        write!(
+            out,
+            "{}\n  {{\"day\": {}, \"total\": {}, \"growth\": {}, \"eigenvalue_gap\": {}, \"by_age\": [{}]}}",
+            if i == 0 { "" } else { "," },
+            snapshot.day,
+            snapshot.total,
+            optional(snapshot.growth, "null"),
+            optional(snapshot.eigenvalue_gap, "null"),
+            by_age.join(", ")
+        )?;
+    }
+
+    writeln!(out, "\n]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [usize; 5] = [3, 4, 3, 1, 2];
+
+    fn snapshots(model: PopulationModel, fish_ages: &[usize]) -> Snapshots {
+        model.snapshots(&model.group_by_age(fish_ages).unwrap())
+    }
+
+    #[test]
+    fn snapshots_follow_population() {
+        let model = PopulationModel::LANTERNFISH;
+        let by_age = model.group_by_age(&EXAMPLE).unwrap();
+
+        for snapshot in snapshots(model, &EXAMPLE).take(257) {
+            let expected = model
+                .calculate_population(by_age.clone(), snapshot.day)
+                .unwrap();
+            assert_eq!(snapshot.total, expected as u128);
+            assert_eq!(snapshot.by_age.iter().sum::<u128>(), snapshot.total);
+        }
+
+        let mut snapshots = snapshots(model, &EXAMPLE);
+        assert_eq!(
+            snapshots.next().unwrap().by_age,
+            [0, 1, 1, 2, 1, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            snapshots.nth(1).unwrap().by_age,
+            [1, 2, 1, 0, 0, 0, 1, 0, 1]
+        );
+    }
+
+    #[test]
+    fn growth_converges_to_eigenvalue() {
+        let model = PopulationModel::LANTERNFISH;
+        let eigenvalue = model.dominant_eigenvalue();
+
+        // The largest root of the characteristic polynomial x^9 - x^2 - 1
+        assert!((eigenvalue.powi(9) - eigenvalue.powi(2) - 1.0).abs() < 1e-9);
+        assert!((eigenvalue - 1.0910).abs() < 1e-4);
+
+        let snapshots: Vec<Snapshot> = snapshots(model, &EXAMPLE).take(257).collect();
+        assert_eq!(snapshots[0].growth, None);
+        assert_eq!(snapshots[2].growth, Some(1.2));
+
+        // The growth oscillates around the eigenvalue with a shrinking amplitude
+        let max_gap = |days: &[Snapshot]| {
+            days.iter()
+                .map(|snapshot| snapshot.eigenvalue_gap.unwrap().abs())
+                .fold(0.0, f64::max)
+        };
+        assert!(max_gap(&snapshots[200..]) < max_gap(&snapshots[100..157]) / 2.0);
+        assert!(max_gap(&snapshots[200..]) < 0.01);
+    }
+
+    #[test]
+    fn eigenvalue_of_other_models() {
+        // Every fish doubles every 3 days, so the growth repeats instead of converging
+        let model = PopulationModel::new(2, 2);
+        assert!((model.dominant_eigenvalue() - 2_f64.powf(1.0 / 3.0)).abs() < 1e-9);
+
+        // Without births the population stays the same
+        let model = PopulationModel::new(2, 2).with_offspring(0);
+        assert_eq!(model.dominant_eigenvalue(), 1.0);
+    }
+
+    #[test]
+    fn stops_before_overflowing() {
+        let mut snapshots = snapshots(PopulationModel::LANTERNFISH, &EXAMPLE);
+        let last = snapshots.by_ref().last().unwrap();
+
+        assert!(last.total > u64::MAX as u128);
+        assert!(last.total < u128::MAX);
+        assert_eq!(snapshots.overflowed_on(), Some(last.day + 1));
+        assert_eq!(snapshots.next(), None);
+    }
+
+    #[test]
+    fn overflow_is_only_reported_once_reached() {
+        let mut snapshots = snapshots(PopulationModel::LANTERNFISH, &EXAMPLE);
+
+        assert_eq!(snapshots.by_ref().take(257).count(), 257);
+        assert_eq!(snapshots.overflowed_on(), None);
+    }
+
+    #[test]
+    fn writes_csv() {
+        let mut out = vec![];
+        write_series(
+            SeriesFormat::Csv,
+            snapshots(PopulationModel::new(1, 2), &[0]).take(3),
+            &mut out,
+        )
+        .unwrap();
+
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "day,total,growth,eigenvalue_gap,age_0,age_1,age_2"
+        );
+        assert_eq!(lines[1], "0,1,,,1,0,0");
+        assert!(lines[2].starts_with("1,2,2,"));
+        assert!(lines[2].ends_with(",0,1,1"));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn writes_json() {
+        let mut out = vec![];
+        write_series(
+            SeriesFormat::Json,
+            snapshots(PopulationModel::new(1, 2).with_offspring(0), &[0]).take(2),
+            &mut out,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n  \
+            {\"day\": 0, \"total\": 1, \"growth\": null, \"eigenvalue_gap\": null, \"by_age\": [1, 0, 0]},\n  \
+            {\"day\": 1, \"total\": 1, \"growth\": 1, \"eigenvalue_gap\": 0, \"by_age\": [0, 1, 0]}\n\
+            ]\n"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+pub use crate::history::{write_csv, write_json, write_series, SeriesFormat, Snapshot, Snapshots};
+pub use crate::matrix::{Arithmetic, Big, Checked, Modular};
+pub use crate::model::PopulationModel;
+pub use crate::reference::{compare_simulations, Fish, School};
+
+mod history;
+mod matrix;
+mod model;
+mod reference;
+
+pub const PART_1_DAYS: usize = 80;
+pub const PART_2_DAYS: usize = 256;
+
+pub fn fish_after_n_days(fish_ages: &[usize], days: usize) -> Result<usize, String> {
+    PopulationModel::LANTERNFISH.fish_after_n_days(fish_ages, days)
+}
+
+pub fn part_1(fish_ages: &[usize]) -> Result<usize, String> {
+    fish_after_n_days(fish_ages, PART_1_DAYS)
+}
+
+pub fn part_2(fish_ages: &[usize]) -> Result<usize, String> {
+    fish_after_n_days(fish_ages, PART_2_DAYS)
+}
+
+pub fn parse_numbers(line: &str) -> Result<Vec<usize>, String> {
+    line.trim()
+        .split(',')
+        .map(|s| s.parse().map_err(|_| format!("Invalid age {}", s)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [usize; 5] = [3, 4, 3, 1, 2];
+
+    #[test]
+    fn parse_numbers_works() {
+        assert_eq!(parse_numbers("3,4,3,1,2\n"), Ok(EXAMPLE.to_vec()));
+        assert!(parse_numbers("3,x").is_err());
+    }
+
+    #[test]
+    fn fish_after_18_days() {
+        assert_eq!(fish_after_n_days(&EXAMPLE, 18), Ok(26));
+    }
+
+    #[test]
+    fn fish_after_80_days() {
+        assert_eq!(part_1(&EXAMPLE), Ok(5934));
+    }
+
+    #[test]
+    fn fish_after_256_days() {
+        assert_eq!(part_2(&EXAMPLE), Ok(26984457539));
+    }
+}
@@ -1,87 +1,88 @@
-fn main() {
-    use std::fs;
+use day_06::{parse_numbers, part_1, part_2, write_series, PopulationModel, SeriesFormat};
+use std::fs;
+use std::io;
 
-    let fish_ages = parse_numbers(fs::read_to_string("./input.txt").unwrap());
-    let fish = fish_after_n_days(&fish_ages, 256);
+const INPUT: &str = "./input.txt";
+const USAGE: &str = "Usage: day-06 [--series csv|json] [--days N]";
 
-    println!("{}", fish);
+struct Args {
+    series: Option<SeriesFormat>,
+    days: Option<usize>,
 }
 
-const RESET_AGE: usize = 6;
-const NEW_AGE: usize = 8;
-
-fn fish_after_n_days(fish_ages: &[usize], days: usize) -> usize {
-    let by_age = group_by_age(&fish_ages);
-    calculate_population(by_age, days)
-}
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            series: None,
+            days: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("Expected a value for {}", arg))?;
+
+            match arg.as_str() {
+                "--series" => parsed.series = Some(value.parse()?),
+                "--days" => {
+                    parsed.days = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid number of days {}", value))?,
+                    )
+                }
+                other => return Err(format!("Unknown argument {}", other)),
+            }
+        }
 
-fn group_by_age(fish_ages: &[usize]) -> [usize; NEW_AGE+1] {
-    let mut by_age = [0_usize; NEW_AGE+1];
+        if parsed.series.is_none() && parsed.days.is_some() {
+            return Err("--days requires --series".to_string());
+        }
 
-    for age in fish_ages {
-        by_age[*age] += 1;
+        Ok(parsed)
     }
-
-    by_age
 }
 
-fn calculate_population(mut by_age: [usize; NEW_AGE+1], days: usize) -> usize {
-    for _ in 0..days {
-        pass_one_day(&mut by_age);
+fn main() {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
-
-    by_age.into_iter().sum()
 }
 
-fn pass_one_day(by_age: &mut [usize; NEW_AGE+1]) {
-    let amount_zero = by_age[0]; // store how many fish are aged 0 --> that's how many new fish will be born
-
-    for i in 1..by_age.len() {
-        if i == RESET_AGE + 1 { // i = 7
-            by_age[i-1] = by_age[i] + amount_zero; // by_age[6] = by_age[7] + amount_zero --> put all fish aged 7 and those whose age was reset from 0 to age 6
-        } else {
-            by_age[i-1] = by_age[i]; // put fish with the current age into age one day younger
+fn run(args: Args) -> Result<(), String> {
+    let input = fs::read_to_string(INPUT).map_err(|e| format!("{}: {}", INPUT, e))?;
+    let fish_ages = parse_numbers(&input)?;
+
+    if let Some(format) = args.series {
+        let model = PopulationModel::LANTERNFISH;
+        let by_age = model.group_by_age(&fish_ages)?;
+        let days = args.days.unwrap_or(day_06::PART_2_DAYS);
+        let mut snapshots = model.snapshots(&by_age);
+
+        write_series(
+            format,
+            snapshots.by_ref().take(days.saturating_add(1)),
+            &mut io::stdout().lock(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        if let Some(day) = snapshots.overflowed_on().filter(|day| *day <= days) {
+            return Err(format!(
+                "Stopped after day {}, the population on day {} doesn't fit into u128",
+                day - 1,
+                day
+            ));
         }
-    }
-
-    by_age[by_age.len()-1] = amount_zero; // put amount of new fish born by those aged 0 into age 8
-}
-
-fn parse_numbers(line: String) -> Vec<usize> {
-    line.split(",")
-        .map(|s| s.parse().unwrap())
-        .collect()
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn group_by_age_works() {
-        let fish_ages: Vec<usize> = vec![1,2,2,3,3,3,4,4,4,4];
-        let by_age = group_by_age(&fish_ages);
-        assert_eq!([0,1,2,3,4,0,0,0,0], by_age);
+        return Ok(());
     }
 
-    #[test]
-    fn fish_after_18_days() {
-        let fish_ages: Vec<usize> = vec![3,4,3,1,2];
-
-        assert_eq!(fish_after_n_days(&fish_ages, 18), 26);
-    }
+    println!("[Part 1] {}", part_1(&fish_ages)?);
+    println!("[Part 2] {}", part_2(&fish_ages)?);
 
-    #[test]
-    fn fish_after_80_days() {
-        let fish_ages: Vec<usize> = vec![3,4,3,1,2];
-
-        assert_eq!(fish_after_n_days(&fish_ages, 80), 5934);
-    }
-
-    #[test]
-    fn fish_after_256_days() {
-        let fish_ages: Vec<usize> = vec![3,4,3,1,2];
-
-        assert_eq!(fish_after_n_days(&fish_ages, 256), 26984457539);
-    }
+    Ok(())
 }
@@ -0,0 +1,265 @@
+use num_bigint::BigUint;
+
+/// The numbers populations are counted in when fast-forwarding
+pub trait Arithmetic {
+    type Value: Clone + PartialEq;
+
+    fn number(&self, n: usize) -> Self::Value;
+
+    /// `None` if the sum can't be represented
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+
+    /// `None` if the product can't be represented
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+}
+
+/// Counts in `u128`, failing on overflow
+#[derive(Debug, Copy, Clone)]
+pub struct Checked;
+
+impl Arithmetic for Checked {
+    type Value = u128;
+
+    fn number(&self, n: usize) -> u128 {
+        n as u128
+    }
+
+    fn add(&self, a: &u128, b: &u128) -> Option<u128> {
+        a.checked_add(*b)
+    }
+
+    fn mul(&self, a: &u128, b: &u128) -> Option<u128> {
+        a.checked_mul(*b)
+    }
+}
+
+/// Counts exactly, however large the population gets
+#[derive(Debug, Copy, Clone)]
+pub struct Big;
+
+impl Arithmetic for Big {
+    type Value = BigUint;
+
+    fn number(&self, n: usize) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> Option<BigUint> {
+        Some(a + b)
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> Option<BigUint> {
+        Some(a * b)
+    }
+}
+
+/// Counts modulo a positive number
+#[derive(Debug, Copy, Clone)]
+pub struct Modular(u64);
+
+impl Modular {
+    pub fn new(modulus: u64) -> Result<Self, String> {
+        match modulus {
+            0 => Err("Expected a positive modulus".to_string()),
+            modulus => Ok(Modular(modulus)),
+        }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Arithmetic for Modular {
+    type Value = u64;
+
+    fn number(&self, n: usize) -> u64 {
+        (n as u128 % self.0 as u128) as u64
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some(((*a as u128 + *b as u128) % self.0 as u128) as u64)
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some((*a as u128 * *b as u128 % self.0 as u128) as u64)
+    }
+}
+
+/// Approximate counts, used to estimate growth rates
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Float;
+
+impl Arithmetic for Float {
+    type Value = f64;
+
+    fn number(&self, n: usize) -> f64 {
+        n as f64
+    }
+
+    fn add(&self, a: &f64, b: &f64) -> Option<f64> {
+        Some(a + b)
+    }
+
+    fn mul(&self, a: &f64, b: &f64) -> Option<f64> {
+        Some(a * b)
+    }
+}
+
+/// Counts in another arithmetic, keeping the values it can't represent as `None` instead of failing
+///
+/// Counts are never negative, so a sum or a product without a zero factor is at least as large as
+/// each of its terms. A result the wrapped arithmetic can represent is thus exact, even if some
+/// intermediate one isn't.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Saturating<'a, A>(pub(crate) &'a A);
+
+impl<A: Arithmetic> Arithmetic for Saturating<'_, A> {
+    type Value = Option<A::Value>;
+
+    fn number(&self, n: usize) -> Self::Value {
+        Some(self.0.number(n))
+    }
+
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value> {
+        Some(match (a, b) {
+            (Some(a), Some(b)) => self.0.add(a, b),
+            _ => None,
+        })
+    }
+
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value> {
+        let zero = self.0.number(0);
+
+        Some(match (a, b) {
+            (Some(n), _) | (_, Some(n)) if *n == zero => Some(zero),
+            (Some(a), Some(b)) => self.0.mul(a, b),
+            _ => None,
+        })
+    }
+}
+
+/// A square matrix stored row by row
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Matrix<T> {
+    size: usize,
+    entries: Vec<T>,
+}
+
+impl<T: Clone> Matrix<T> {
+    pub(crate) fn from_columns(columns: Vec<Vec<T>>) -> Self {
+        let size = columns.len();
+        let entries = (0..size)
+            .flat_map(|row| columns.iter().map(move |column| column[row].clone()))
+            .collect();
+
+        Self { size, entries }
+    }
+
+    fn row(&self, row: usize) -> &[T] {
+        &self.entries[row * self.size..(row + 1) * self.size]
+    }
+}
+
+/// The dot product of `a` and `b`, `None` if it overflows
+fn dot<A: Arithmetic>(
+    arithmetic: &A,
+    a: impl Iterator<Item = A::Value>,
+    b: impl Iterator<Item = A::Value>,
+) -> Option<A::Value> {
+    a.zip(b).try_fold(arithmetic.number(0), |sum, (a, b)| {
+        arithmetic.add(&sum, &arithmetic.mul(&a, &b)?)
+    })
+}
+
+impl<V: Clone> Matrix<V> {
+    pub(crate) fn mul<A: Arithmetic<Value = V>>(
+        &self,
+        other: &Self,
+        arithmetic: &A,
+    ) -> Option<Self> {
+        let mut entries = Vec::with_capacity(self.entries.len());
+
+        for row in 0..self.size {
+            for column in 0..self.size {
+                let column = (0..self.size).map(|i| other.entries[i * self.size + column].clone());
+                entries.push(dot(arithmetic, self.row(row).iter().cloned(), column)?);
+            }
+        }
+
+        Some(Self {
+            size: self.size,
+            entries,
+        })
+    }
+
+    pub(crate) fn apply<A: Arithmetic<Value = V>>(
+        &self,
+        vector: &[V],
+        arithmetic: &A,
+    ) -> Option<Vec<V>> {
+        (0..self.size)
+            .map(|row| {
+                dot(
+                    arithmetic,
+                    self.row(row).iter().cloned(),
+                    vector.iter().cloned(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: [[u128; 2]; 2]) -> Matrix<u128> {
+        Matrix {
+            size: 2,
+            entries: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    #[test]
+    fn multiplies_matrices() {
+        let a = matrix([[1, 2], [3, 4]]);
+        let b = matrix([[0, 1], [1, 0]]);
+
+        assert_eq!(a.mul(&b, &Checked), Some(matrix([[2, 1], [4, 3]])));
+        assert_eq!(a.apply(&[1, 1], &Checked), Some(vec![3, 7]));
+        assert_eq!(
+            Matrix::from_columns(vec![vec![1, 3], vec![2, 4]]),
+            matrix([[1, 2], [3, 4]])
+        );
+    }
+
+    #[test]
+    fn saturates_exactly() {
+        let saturating = Saturating(&Checked);
+        let huge = saturating.mul(&Some(u128::MAX), &Some(2)).unwrap();
+
+        assert_eq!(huge, None);
+        assert_eq!(saturating.add(&huge, &Some(0)), Some(None));
+        assert_eq!(saturating.mul(&huge, &Some(0)), Some(Some(0)));
+        assert_eq!(saturating.mul(&Some(3), &Some(4)), Some(Some(12)));
+    }
+
+    #[test]
+    fn detects_overflow() {
+        let a = matrix([[u128::MAX, 0], [0, 1]]);
+
+        assert_eq!(a.mul(&matrix([[2, 0], [0, 1]]), &Checked), None);
+        assert_eq!(a.apply(&[1, 0], &Checked), Some(vec![u128::MAX, 0]));
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        let modular = Modular::new(7).unwrap();
+
+        assert_eq!(modular.number(20), 6);
+        assert_eq!(modular.add(&5, &4), Some(2));
+        assert_eq!(modular.mul(&5, &4), Some(6));
+        assert!(Modular::new(0).is_err());
+    }
+}
@@ -0,0 +1,452 @@
+use crate::history::Snapshots;
+use crate::matrix::{Arithmetic, Float, Matrix, Saturating};
+
+const OVERFLOW: &str = "The population overflows";
+
+/// A population whose members breed in a fixed cycle, counted in buckets of fish sharing a state
+///
+/// Every fish has a timer counting down the days until it gives birth. When a timer at 0 passes a
+/// day, it's reset to `reset_age` and `offspring` fish are born with timers at `newborn_age`. If
+/// `max_lifespan` is set, fish die after living that many days, still giving birth on their last
+/// day. Fish present at the start are treated as being born on day 0.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PopulationModel {
+    pub reset_age: usize,
+    pub newborn_age: usize,
+    pub offspring: usize,
+    pub max_lifespan: Option<usize>,
+}
+
+impl PopulationModel {
+    /// The lanternfish of the puzzle, breeding every 7 days and needing 2 extra days when newborn
+    pub const LANTERNFISH: PopulationModel = PopulationModel {
+        reset_age: 6,
+        newborn_age: 8,
+        offspring: 1,
+        max_lifespan: None,
+    };
+
+    /// A model with one offspring per birth and unlimited lifespans
+    pub fn new(reset_age: usize, newborn_age: usize) -> Self {
+        Self {
+            reset_age,
+            newborn_age,
+            offspring: 1,
+            max_lifespan: None,
+        }
+    }
+
+    pub fn with_offspring(self, offspring: usize) -> Self {
+        Self { offspring, ..self }
+    }
+
+    pub fn with_max_lifespan(self, max_lifespan: usize) -> Self {
+        Self {
+            max_lifespan: Some(max_lifespan),
+            ..self
+        }
+    }
+
+    /// The number of different timer values
+    pub fn timers(&self) -> usize {
+        self.reset_age.max(self.newborn_age) + 1
+    }
+
+    /// The number of buckets, one per timer and, if fish die, per number of days lived
+    pub fn states(&self) -> usize {
+        self.timers() * self.max_lifespan.unwrap_or(1)
+    }
+
+    fn index(&self, timer: usize, lived: usize) -> usize {
+        lived * self.timers() + timer
+    }
+
+    /// Counts the fish per bucket, `fish_ages` being the timers of the fish
+    pub fn group_by_age(&self, fish_ages: &[usize]) -> Result<Vec<usize>, String> {
+        if self.max_lifespan == Some(0) {
+            return Err("Expected a positive lifespan".to_string());
+        }
+
+        let mut by_age = vec![0; self.states()];
+
+        for age in fish_ages {
+            if *age >= self.timers() {
+                return Err(format!("Invalid age {}", age));
+            }
+
+            by_age[self.index(*age, 0)] += 1;
+        }
+
+        Ok(by_age)
+    }
+
+    /// The buckets a fish in bucket `i` turns into after a day, with the number of fish in each
+    fn successors(&self, i: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (timer, lived) = (i % self.timers(), i / self.timers());
+        let next_lived = match self.max_lifespan {
+            Some(max_lifespan) if lived + 1 >= max_lifespan => None,
+            Some(_) => Some(lived + 1),
+            None => Some(0),
+        };
+        let next_timer = match timer {
+            0 => self.reset_age,
+            timer => timer - 1,
+        };
+
+        let births = (timer == 0).then(|| (self.index(self.newborn_age, 0), self.offspring));
+        let survivors = next_lived.map(|next_lived| (self.index(next_timer, next_lived), 1));
+
+        births.into_iter().chain(survivors)
+    }
+
+    /// Fails if a bucket overflows, leaving `by_age` unchanged
+    pub fn pass_one_day(&self, by_age: &mut [usize]) -> Result<(), String> {
+        let mut next = vec![0_usize; by_age.len()];
+
+        for (i, count) in by_age.iter().enumerate() {
+            for (bucket, fish) in self.successors(i) {
+                next[bucket] = count
+                    .checked_mul(fish)
+                    .and_then(|born| next[bucket].checked_add(born))
+                    .ok_or(OVERFLOW)?;
+            }
+        }
+
+        by_age.copy_from_slice(&next);
+
+        Ok(())
+    }
+
+    pub fn calculate_population(
+        &self,
+        mut by_age: Vec<usize>,
+        days: usize,
+    ) -> Result<usize, String> {
+        for _ in 0..days {
+            self.pass_one_day(&mut by_age)?;
+        }
+
+        by_age
+            .into_iter()
+            .try_fold(0_usize, |sum, n| sum.checked_add(n))
+            .ok_or_else(|| OVERFLOW.to_string())
+    }
+
+    pub fn fish_after_n_days(&self, fish_ages: &[usize], days: usize) -> Result<usize, String> {
+        let by_age = self.group_by_age(fish_ages)?;
+
+        self.calculate_population(by_age, days)
+    }
+
+    /// The matrix turning the buckets of one day into those of the next, column `i` being the
+    /// buckets a single fish in bucket `i` turns into
+    ///
+    /// `None` if `arithmetic` can't represent an entry, which are at most `offspring + 1`.
+    pub(crate) fn transition_matrix<A: Arithmetic>(
+        &self,
+        arithmetic: &A,
+    ) -> Option<Matrix<A::Value>> {
+        let columns = (0..self.states())
+            .map(|i| {
+                let mut column = vec![arithmetic.number(0); self.states()];
+
+                for (bucket, fish) in self.successors(i) {
+                    column[bucket] = arithmetic.add(&column[bucket], &arithmetic.number(fish))?;
+                }
+
+                Some(column)
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Matrix::from_columns(columns))
+    }
+
+    /// Counts the population after `days` by raising the transition matrix to the power of `days`,
+    /// taking a logarithmic number of matrix multiplications
+    ///
+    /// A power of the matrix may overflow even if the population fits, as long as the fish haven't
+    /// reached the fastest growing states yet. Counting in [`Saturating`] carries such values along
+    /// as too large, so only a population which doesn't fit itself is reported as overflowing.
+    pub fn fast_forward<A: Arithmetic>(
+        &self,
+        arithmetic: &A,
+        by_age: &[usize],
+        days: usize,
+    ) -> Result<A::Value, String> {
+        self.try_fast_forward(&Saturating(arithmetic), by_age, days)
+            .flatten()
+            .ok_or_else(|| OVERFLOW.to_string())
+    }
+
+    fn try_fast_forward<A: Arithmetic>(
+        &self,
+        arithmetic: &A,
+        by_age: &[usize],
+        mut days: usize,
+    ) -> Option<A::Value> {
+        let mut power = self.transition_matrix(arithmetic)?;
+        let mut by_age: Vec<A::Value> = by_age.iter().map(|n| arithmetic.number(*n)).collect();
+
+        while days > 0 {
+            if days & 1 == 1 {
+                by_age = power.apply(&by_age, arithmetic)?;
+            }
+
+            days >>= 1;
+            if days > 0 {
+                power = power.mul(&power, arithmetic)?;
+            }
+        }
+
+        by_age
+            .iter()
+            .try_fold(arithmetic.number(0), |sum, n| arithmetic.add(&sum, n))
+    }
+
+    pub fn fish_after_n_days_with<A: Arithmetic>(
+        &self,
+        arithmetic: &A,
+        fish_ages: &[usize],
+        days: usize,
+    ) -> Result<A::Value, String> {
+        let by_age = self.group_by_age(fish_ages)?;
+
+        self.fast_forward(arithmetic, &by_age, days)
+    }
+
+    /// The daily snapshots of the population, starting with `by_age` on day 0
+    pub fn snapshots(&self, by_age: &[usize]) -> Snapshots {
+        Snapshots::new(self, by_age)
+    }
+
+    /// The factor the population grows by each day in the long run
+    ///
+    /// Found by power iteration on the transition matrix plus the identity, which has the same
+    /// eigenvectors and converges even if all fish breed in cycles of the same length.
+    pub fn dominant_eigenvalue(&self) -> f64 {
+        const MAX_ITERATIONS: usize = 100_000;
+        const EPSILON: f64 = 1e-13;
+
+        let matrix = self.transition_matrix(&Float).unwrap();
+        let mut vector = vec![1.0 / self.states() as f64; self.states()];
+        let mut eigenvalue = 0.0;
+
+        for _ in 0..MAX_ITERATIONS {
+            let next: Vec<f64> = matrix
+                .apply(&vector, &Float)
+                .unwrap()
+                .iter()
+                .zip(&vector)
+                .map(|(a, b)| a + b)
+                .collect();
+            let sum: f64 = next.iter().sum();
+            let next: Vec<f64> = next.into_iter().map(|n| n / sum).collect();
+            let converged = next
+                .iter()
+                .zip(&vector)
+                .all(|(a, b)| (a - b).abs() < EPSILON);
+
+            eigenvalue = sum - 1.0;
+            vector = next;
+
+            if converged {
+                break;
+            }
+        }
+
+        eigenvalue
+    }
+}
+
+impl Default for PopulationModel {
+    fn default() -> Self {
+        Self::LANTERNFISH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::{Big, Checked, Modular};
+    use num_bigint::BigUint;
+    use std::time::{Duration, Instant};
+
+    const EXAMPLE: [usize; 5] = [3, 4, 3, 1, 2];
+
+    #[test]
+    fn group_by_age_works() {
+        let fish_ages: Vec<usize> = vec![1, 2, 2, 3, 3, 3, 4, 4, 4, 4];
+        let by_age = PopulationModel::LANTERNFISH.group_by_age(&fish_ages);
+
+        assert_eq!(by_age, Ok(vec![0, 1, 2, 3, 4, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn rejects_invalid_ages() {
+        assert!(PopulationModel::LANTERNFISH.group_by_age(&[9]).is_err());
+        assert!(PopulationModel::new(2, 3).group_by_age(&[3]).is_ok());
+        assert!(PopulationModel::LANTERNFISH
+            .with_max_lifespan(0)
+            .group_by_age(&[1])
+            .is_err());
+    }
+
+    #[test]
+    fn lanternfish_after_18_days() {
+        assert_eq!(
+            PopulationModel::LANTERNFISH.fish_after_n_days(&EXAMPLE, 18),
+            Ok(26)
+        );
+    }
+
+    #[test]
+    fn loop_reports_overflow() {
+        let model = PopulationModel::LANTERNFISH;
+
+        assert!(model.fish_after_n_days(&EXAMPLE, 256).is_ok());
+        assert!(model.fish_after_n_days(&EXAMPLE, 1000).is_err());
+
+        // Fish resetting their timers join the ones at 7, which no longer fit
+        let mut by_age = vec![usize::MAX, 0, 0, 0, 0, 0, 0, 1, 0];
+        assert!(model.pass_one_day(&mut by_age).is_err());
+        assert_eq!(by_age, [usize::MAX, 0, 0, 0, 0, 0, 0, 1, 0]);
+        assert!(model
+            .with_offspring(usize::MAX)
+            .fish_after_n_days(&[0, 0], 1)
+            .is_err());
+    }
+
+    #[test]
+    fn counts_offspring() {
+        // A single fish giving birth to 3 fish on day 1, which give birth on day 3
+        let model = PopulationModel::new(5, 1).with_offspring(3);
+
+        assert_eq!(model.fish_after_n_days(&[0], 1), Ok(4));
+        assert_eq!(model.fish_after_n_days(&[0], 2), Ok(4));
+        assert_eq!(model.fish_after_n_days(&[0], 3), Ok(13));
+    }
+
+    #[test]
+    fn fish_die_of_old_age() {
+        let model = PopulationModel::new(2, 2).with_max_lifespan(4);
+
+        // Born on day 0 and giving birth on days 1 and 4, right before dying
+        assert_eq!(model.fish_after_n_days(&[0], 1), Ok(2));
+        assert_eq!(model.fish_after_n_days(&[0], 3), Ok(2));
+        assert_eq!(model.fish_after_n_days(&[0], 4), Ok(3));
+        assert_eq!(model.fish_after_n_days(&[2], 4), Ok(1));
+        assert_eq!(model.fish_after_n_days(&[2], 6), Ok(2));
+    }
+
+    #[test]
+    fn long_lifespans_change_nothing() {
+        let model = PopulationModel::LANTERNFISH.with_max_lifespan(100);
+
+        assert_eq!(
+            model.fish_after_n_days(&EXAMPLE, 80),
+            PopulationModel::LANTERNFISH.fish_after_n_days(&EXAMPLE, 80)
+        );
+    }
+
+    #[test]
+    fn fast_forward_matches_loop() {
+        let modular = Modular::new(1_000_000_007).unwrap();
+        // Every day for the puzzle, some days for models with many more buckets
+        let models = [
+            (
+                PopulationModel::LANTERNFISH,
+                &EXAMPLE[..],
+                (0..=256).step_by(1),
+            ),
+            (
+                PopulationModel::new(2, 3).with_max_lifespan(8),
+                &[0, 3, 3][..],
+                (0..=128).step_by(3),
+            ),
+            (
+                PopulationModel::new(4, 5).with_offspring(2),
+                &EXAMPLE[..],
+                (0..=128).step_by(1),
+            ),
+            (
+                PopulationModel::new(1, 0),
+                &[0, 1, 1][..],
+                (0..=64).step_by(1),
+            ),
+        ];
+
+        for (model, fish_ages, days) in models {
+            let by_age = model.group_by_age(fish_ages).unwrap();
+
+            for days in days {
+                let expected = model.calculate_population(by_age.clone(), days).unwrap();
+
+                assert_eq!(
+                    model.fast_forward(&Checked, &by_age, days),
+                    Ok(expected as u128)
+                );
+                assert_eq!(
+                    model.fast_forward(&Big, &by_age, days),
+                    Ok(BigUint::from(expected))
+                );
+                assert_eq!(
+                    model.fast_forward(&modular, &by_age, days),
+                    Ok(expected as u64 % modular.modulus())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fast_forwards_far() {
+        let model = PopulationModel::LANTERNFISH;
+        let modular = Modular::new(1_000_000_007).unwrap();
+
+        let big = model.fish_after_n_days_with(&Big, &EXAMPLE, 2000).unwrap();
+        assert_eq!(
+            model.fish_after_n_days_with(&modular, &EXAMPLE, 2000),
+            Ok((big % modular.modulus()).try_into().unwrap())
+        );
+
+        assert!(model
+            .fish_after_n_days_with(&Checked, &EXAMPLE, 2000)
+            .is_err());
+        assert!(model
+            .fish_after_n_days_with(&modular, &EXAMPLE, 1_000_000_000_000)
+            .is_ok());
+    }
+
+    #[test]
+    fn overflows_far_ahead_quickly() {
+        let start = Instant::now();
+
+        assert!(PopulationModel::LANTERNFISH
+            .fish_after_n_days_with(&Checked, &EXAMPLE, 1_000_000_000_000)
+            .is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn fast_forward_survives_overflowing_powers() {
+        let model = PopulationModel::new(1, 1)
+            .with_offspring(1000)
+            .with_max_lifespan(4);
+        let mut by_age = model.group_by_age(&[0]).unwrap();
+
+        // The matrix powers overflow after a few dozen days, long before the population does
+        assert!(model.fast_forward(&Checked, &by_age, 100).is_err());
+        assert_eq!(
+            model.fast_forward(&Checked, &by_age, 8),
+            Ok(model.calculate_population(by_age.clone(), 8).unwrap() as u128)
+        );
+
+        // A fish on its last day without giving birth dies, leaving an empty population
+        by_age = vec![0; model.states()];
+        by_age[3 * model.timers() + 1] = 1;
+        assert_eq!(model.fast_forward(&Checked, &by_age, 100), Ok(0));
+        assert_eq!(
+            model.fast_forward(&Big, &by_age, 100),
+            Ok(BigUint::from(0_u8))
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::PopulationModel;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Fish {
+    pub id: usize,
+    /// `None` for the fish present on day 0
+    pub parent: Option<usize>,
+    pub born: usize,
+    /// The day the fish didn't live to see
+    pub died: Option<usize>,
+    pub timer: usize,
+    pub lived: usize,
+}
+
+/// Simulates every fish on its own, remembering who gave birth to whom
+///
+/// Much slower than counting buckets and only meant for checking [`PopulationModel`].
+#[derive(Debug, Clone)]
+pub struct School {
+    model: PopulationModel,
+    day: usize,
+    /// Every fish that ever lived, indexed by id
+    fish: Vec<Fish>,
+    children: Vec<Vec<usize>>,
+}
+
+impl School {
+    pub fn new(model: PopulationModel, fish_ages: &[usize]) -> Result<Self, String> {
+        // Validates the ages and the model
+        model.group_by_age(fish_ages)?;
+
+        let fish = fish_ages
+            .iter()
+            .enumerate()
+            .map(|(id, timer)| Fish {
+                id,
+                parent: None,
+                born: 0,
+                died: None,
+                timer: *timer,
+                lived: 0,
+            })
+            .collect();
+
+        Ok(Self {
+            model,
+            day: 0,
+            fish,
+            children: vec![vec![]; fish_ages.len()],
+        })
+    }
+
+    pub fn day(&self) -> usize {
+        self.day
+    }
+
+    pub fn pass_one_day(&mut self) {
+        self.day += 1;
+
+        for id in 0..self.fish.len() {
+            let fish = &mut self.fish[id];
+            if fish.died.is_some() {
+                continue;
+            }
+
+            let gives_birth = fish.timer == 0;
+            fish.timer = match fish.timer {
+                0 => self.model.reset_age,
+                timer => timer - 1,
+            };
+            fish.lived += 1;
+
+            if self.model.max_lifespan == Some(fish.lived) {
+                fish.died = Some(self.day);
+            }
+
+            if gives_birth {
+                for _ in 0..self.model.offspring {
+                    let child = self.fish.len();
+
+                    self.fish.push(Fish {
+                        id: child,
+                        parent: Some(id),
+                        born: self.day,
+                        died: None,
+                        timer: self.model.newborn_age,
+                        lived: 0,
+                    });
+                    self.children.push(vec![]);
+                    self.children[id].push(child);
+                }
+            }
+        }
+    }
+
+    pub fn alive(&self) -> impl Iterator<Item = &Fish> {
+        self.fish.iter().filter(|fish| fish.died.is_none())
+    }
+
+    pub fn population(&self) -> usize {
+        self.alive().count()
+    }
+
+    /// Counts the living fish in the buckets of [`PopulationModel::group_by_age`]
+    pub fn by_age(&self) -> Vec<usize> {
+        let mut by_age = vec![0; self.model.states()];
+
+        for fish in self.alive() {
+            let lived = match self.model.max_lifespan {
+                Some(_) => fish.lived,
+                None => 0,
+            };
+
+            by_age[lived * self.model.timers() + fish.timer] += 1;
+        }
+
+        by_age
+    }
+
+    pub fn fish(&self, id: usize) -> Option<&Fish> {
+        self.fish.get(id)
+    }
+
+    pub fn children(&self, id: usize) -> &[usize] {
+        self.children.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The ids of the parent, grandparent and so on of fish `id`
+    pub fn ancestors(&self, id: usize) -> Vec<usize> {
+        let mut ancestors = vec![];
+        let mut parent = self.fish(id).and_then(|fish| fish.parent);
+
+        while let Some(id) = parent {
+            ancestors.push(id);
+            parent = self.fish[id].parent;
+        }
+
+        ancestors
+    }
+
+    /// The ids of all children, grandchildren and so on of fish `id`, alive or not
+    pub fn descendants(&self, id: usize) -> Vec<usize> {
+        let mut descendants = vec![];
+        let mut stack = self.children(id).to_vec();
+
+        while let Some(id) = stack.pop() {
+            descendants.push(id);
+            stack.extend(self.children(id));
+        }
+
+        descendants.sort_unstable();
+        descendants
+    }
+}
+
+/// Runs `model` for up to `days` days with both the bucket and the individual simulation,
+/// comparing the buckets every day
+///
+/// Stops early once more than `max_population` fish lived, returning the number of days compared.
+pub fn compare_simulations(
+    model: PopulationModel,
+    fish_ages: &[usize],
+    days: usize,
+    max_population: usize,
+) -> Result<usize, String> {
+    let mut by_age = model.group_by_age(fish_ages)?;
+    let mut school = School::new(model, fish_ages)?;
+
+    let mut day = 0;
+
+    loop {
+        if school.by_age() != by_age {
+            return Err(format!(
+                "Simulations differ on day {} of {:?}: {:?} counted in buckets, {:?} one by one",
+                day,
+                model,
+                by_age,
+                school.by_age()
+            ));
+        }
+
+        if day == days || school.fish.len() > max_population {
+            return Ok(day);
+        }
+
+        model.pass_one_day(&mut by_age)?;
+        school.pass_one_day();
+        day += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn tracks_family_tree() {
+        let mut school = School::new(PopulationModel::LANTERNFISH, &[0, 3]).unwrap();
+
+        for _ in 0..9 {
+            school.pass_one_day();
+        }
+
+        // Fish 0 gave birth on days 1 and 8, fish 1 on day 4
+        assert_eq!(school.day(), 9);
+        assert_eq!(school.population(), 5);
+        assert_eq!(school.children(0), [2, 4]);
+        assert_eq!(school.children(1), [3]);
+        assert_eq!(school.fish(4).unwrap().born, 8);
+        assert_eq!(school.fish(4).unwrap().parent, Some(0));
+
+        for _ in 0..9 {
+            school.pass_one_day();
+        }
+
+        // Fish 2 was born on day 1 and gave birth on day 10
+        let grandchild = *school.children(2).first().unwrap();
+        assert_eq!(school.fish(grandchild).unwrap().born, 10);
+        assert_eq!(school.ancestors(grandchild), [2, 0]);
+        assert!(school.descendants(0).contains(&grandchild));
+        assert!(!school.descendants(1).contains(&grandchild));
+        assert_eq!(
+            school.descendants(0).len() + school.descendants(1).len() + 2,
+            school.population()
+        );
+    }
+
+    #[test]
+    fn remembers_dead_fish() {
+        let mut school =
+            School::new(PopulationModel::new(2, 2).with_max_lifespan(4), &[0]).unwrap();
+
+        for _ in 0..4 {
+            school.pass_one_day();
+        }
+
+        assert_eq!(school.fish(0).unwrap().died, Some(4));
+        // Fish 1 was born on day 1 and gave birth on day 4 as well
+        assert_eq!(school.children(0), [1, 2]);
+        assert_eq!(school.children(1), [3]);
+        assert_eq!(school.population(), 3);
+    }
+
+    #[test]
+    fn matches_bucket_simulation_on_example() {
+        assert_eq!(
+            compare_simulations(
+                PopulationModel::LANTERNFISH,
+                &[3, 4, 3, 1, 2],
+                80,
+                usize::MAX
+            ),
+            Ok(80)
+        );
+    }
+
+    /// The number of days to compare, overridden by `DAY_06_DIFFERENTIAL_DAYS`
+    fn day_limit() -> usize {
+        std::env::var("DAY_06_DIFFERENTIAL_DAYS")
+            .ok()
+            .and_then(|days| days.parse().ok())
+            .unwrap_or(100)
+    }
+
+    fn model() -> impl Strategy<Value = PopulationModel> {
+        (
+            0..8_usize,
+            0..10_usize,
+            0..4_usize,
+            prop::option::of(1..30_usize),
+        )
+            .prop_map(|(reset_age, newborn_age, offspring, max_lifespan)| {
+                PopulationModel {
+                    reset_age,
+                    newborn_age,
+                    offspring,
+                    max_lifespan,
+                }
+            })
+    }
+
+    fn model_and_ages() -> impl Strategy<Value = (PopulationModel, Vec<usize>)> {
+        model()
+            .prop_flat_map(|model| (Just(model), prop::collection::vec(0..model.timers(), 0..20)))
+    }
+
+    proptest! {
+        #[test]
+        fn matches_bucket_simulation((model, fish_ages) in model_and_ages()) {
+            let result = compare_simulations(model, &fish_ages, day_limit(), 20_000);
+
+            prop_assert!(result.is_ok(), "{}", result.unwrap_err());
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use day_07::{optimal_position, optimal_position_brute_force, Part1, Part2Triangular};
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+/// Generates `crabs` positions in `0..range`
+fn generate(crabs: usize, range: usize) -> Vec<usize> {
+    prop::collection::vec(0..range, crabs)
+        .new_tree(&mut TestRunner::deterministic())
+        .unwrap()
+        .current()
+}
+
+pub fn optimal_position_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("optimal_position");
+    group.sample_size(10);
+
+    for crabs in [1_000, 10_000] {
+        let numbers = generate(crabs, crabs);
+
+        assert_eq!(
+            optimal_position(&Part1, numbers.clone()),
+            optimal_position_brute_force(&Part1, numbers.clone())
+        );
+        assert_eq!(
+            optimal_position(&Part2Triangular, numbers.clone()),
+            optimal_position_brute_force(&Part2Triangular, numbers.clone())
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("part_1_brute_force", crabs),
+            &numbers,
+            |b, numbers| {
+                b.iter(|| optimal_position_brute_force(&Part1, black_box(numbers.clone())))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("part_1_median", crabs),
+            &numbers,
+            |b, numbers| b.iter(|| optimal_position(&Part1, black_box(numbers.clone()))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("part_2_brute_force", crabs),
+            &numbers,
+            |b, numbers| {
+                b.iter(|| {
+                    optimal_position_brute_force(&Part2Triangular, black_box(numbers.clone()))
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("part_2_mean", crabs),
+            &numbers,
+            |b, numbers| b.iter(|| optimal_position(&Part2Triangular, black_box(numbers.clone()))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, optimal_position_bench);
+criterion_main!(benches);
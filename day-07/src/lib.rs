@@ -1,47 +1,101 @@
 use std::ops::Sub;
-use crate::minmax::MinMax;
 
-mod minmax;
+pub use crate::model::{CostModel, Piecewise, Quadratic, Weighted};
+pub use crate::solver::Strategy;
 
-pub fn optimal_position<F: FuelCalculator>(numbers: Vec<usize>) -> Option<(usize, usize)> {
-    let (min, max) = numbers.iter().min_max()?;
+mod minmax;
+mod model;
+mod solver;
+
+/// Finds the position needing the least fuel and the fuel needed, using the strategy of `calculator`
+///
+/// Fails if even the cheapest position needs more fuel than fits into `u128`.
+pub fn optimal_position<F: FuelCalculator + ?Sized>(
+    calculator: &F,
+    numbers: Vec<usize>,
+) -> Result<Option<(usize, u128)>, String> {
+    calculator.strategy().solve(calculator, numbers)
+}
 
-    (*min..=*max)
-        .map(|position| (position, fuel_consumption::<F, _>(&numbers, position)))
-        .min_by_key(|(_, fuel)| *fuel)
+/// Finds the position needing the least fuel by trying every position
+pub fn optimal_position_brute_force<F: FuelCalculator + ?Sized>(
+    calculator: &F,
+    numbers: Vec<usize>,
+) -> Result<Option<(usize, u128)>, String> {
+    Strategy::BruteForce.solve(calculator, numbers)
 }
 
-fn fuel_consumption<'a, F: FuelCalculator, I: IntoIterator<Item = &'a usize>>(numbers: I, x: usize) -> usize {
-    numbers.into_iter().map(|n| F::fuel(*n, x)).sum()
+/// The fuel all crabs need to get to `x`, failing if it doesn't fit into `u128`
+pub fn fuel_consumption<'a, F: FuelCalculator + ?Sized, I: IntoIterator<Item = &'a usize>>(
+    calculator: &F,
+    numbers: I,
+    x: usize,
+) -> Result<u128, String> {
+    numbers
+        .into_iter()
+        .enumerate()
+        .try_fold(0_u128, |sum, (crab, n)| {
+            sum.checked_add(
+                calculator
+                    .fuel(*n, x)
+                    .checked_mul(calculator.weight(crab))?,
+            )
+        })
+        .ok_or_else(|| format!("The fuel needed to get to {} overflows", x))
 }
 
 pub trait FuelCalculator {
-    fn fuel(position: usize, target: usize) -> usize;
+    /// How [`optimal_position`] searches for the cheapest position
+    fn strategy(&self) -> Strategy {
+        Strategy::BruteForce
+    }
+
+    /// How many times the crab at index `crab` needs the fuel of [`FuelCalculator::fuel`]
+    fn weight(&self, _crab: usize) -> u128 {
+        1
+    }
+
+    /// The fuel one crab needs, which is exact as long as it grows at most quadratically
+    fn fuel(&self, position: usize, target: usize) -> u128;
 }
 
+/// Costs proportional to the distance
 pub struct Part1;
 
 impl FuelCalculator for Part1 {
-    fn fuel(position: usize, target: usize) -> usize {
-        position.diff(target)
+    fn strategy(&self) -> Strategy {
+        Strategy::Median
+    }
+
+    fn fuel(&self, position: usize, target: usize) -> u128 {
+        position.diff(target) as u128
     }
 }
 
 pub struct Part2ManualSum;
 
 impl FuelCalculator for Part2ManualSum {
-    fn fuel(position: usize, target: usize) -> usize {
-        (1..=position.diff(target)).sum()
+    fn strategy(&self) -> Strategy {
+        Strategy::Mean
+    }
+
+    fn fuel(&self, position: usize, target: usize) -> u128 {
+        (1..=position.diff(target) as u128).sum()
     }
 }
 
+/// Costs growing by one with every step
 pub struct Part2Triangular;
 
 impl FuelCalculator for Part2Triangular {
-    fn fuel(position: usize, target: usize) -> usize {
-        let diff = position.diff(target) as f64;
-        let fuel = (diff.powi(2) + diff) / 2.0;
-        fuel as usize
+    fn strategy(&self) -> Strategy {
+        Strategy::Mean
+    }
+
+    /// Never overflows, since `diff * (diff + 1)` is less than `2^128` for any `usize`
+    fn fuel(&self, position: usize, target: usize) -> u128 {
+        let diff = position.diff(target) as u128;
+        diff * (diff + 1) / 2
     }
 }
 
@@ -71,37 +125,93 @@ impl<T: Ord + Sub + Sized> Diff for T {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn optimal_position_part_1() {
-        let positions = vec![16,1,2,0,4,2,7,1,2,14];
-        assert_eq!(Some((2, 37)), optimal_position::<Part1>(positions));
+        let positions = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(Ok(Some((2, 37))), optimal_position(&Part1, positions));
     }
 
     #[test]
     fn optimal_position_part_2() {
-        let positions = vec![16,1,2,0,4,2,7,1,2,14];
-        assert_eq!(Some((5, 168)), optimal_position::<Part2Triangular>(positions));
+        let positions = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(
+            Ok(Some((5, 168))),
+            optimal_position(&Part2Triangular, positions)
+        );
     }
 
     #[test]
     fn fuel_consumption_part_1() {
-        let positions = vec![1,2,4,5];
-        assert_eq!(fuel_consumption::<Part1, _>(&positions, 3), 6);
-        assert_eq!(fuel_consumption::<Part1, _>(&positions, 1), 8);
+        let positions = vec![1, 2, 4, 5];
+        assert_eq!(fuel_consumption(&Part1, &positions, 3), Ok(6));
+        assert_eq!(fuel_consumption(&Part1, &positions, 1), Ok(8));
     }
 
     #[test]
     fn fuel_consumption_part_2_manual_sum() {
-        let positions = vec![1,2,4,5];
-        assert_eq!(fuel_consumption::<Part2ManualSum, _>(&positions, 3), 8);
-        assert_eq!(fuel_consumption::<Part2ManualSum, _>(&positions, 1), 17);
+        let positions = vec![1, 2, 4, 5];
+        assert_eq!(fuel_consumption(&Part2ManualSum, &positions, 3), Ok(8));
+        assert_eq!(fuel_consumption(&Part2ManualSum, &positions, 1), Ok(17));
     }
 
     #[test]
     fn fuel_consumption_part_2_triangular() {
-        let positions = vec![1,2,4,5];
-        assert_eq!(fuel_consumption::<Part2Triangular, _>(&positions, 3), 8);
-        assert_eq!(fuel_consumption::<Part2Triangular, _>(&positions, 1), 17);
+        let positions = vec![1, 2, 4, 5];
+        assert_eq!(fuel_consumption(&Part2Triangular, &positions, 3), Ok(8));
+        assert_eq!(fuel_consumption(&Part2Triangular, &positions, 1), Ok(17));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fuel_is_exact_beyond_f64_precision() {
+        // 2^53 + 1 is the first integer an f64 can't represent
+        let diff = (1 << 53) + 1_usize;
+        let expected = diff as u128 * (diff as u128 + 1) / 2;
+
+        assert_eq!(Part2Triangular.fuel(0, diff), expected);
+        assert_eq!(Part2Triangular.fuel(usize::MAX, 0) % 2, 0);
+        assert_eq!(Part1.fuel(usize::MAX, 0), usize::MAX as u128);
+    }
+
+    #[test]
+    fn fuel_consumption_overflow() {
+        let positions = vec![usize::MAX; 4];
+
+        assert_eq!(
+            fuel_consumption(&Part2Triangular, &positions, 0),
+            Err(format!("The fuel needed to get to {} overflows", 0))
+        );
+        assert_eq!(
+            fuel_consumption(&Part1, &positions, 0),
+            Ok(4 * usize::MAX as u128)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn triangular_matches_manual_sum(position in 0..5000_usize, target in 0..5000_usize) {
+            prop_assert_eq!(
+                Part2Triangular.fuel(position, target),
+                Part2ManualSum.fuel(position, target)
+            );
+        }
+
+        #[test]
+        fn triangular_grows_by_the_distance(diff in 1..=usize::MAX) {
+            prop_assert_eq!(
+                Part2Triangular.fuel(0, diff) - Part2Triangular.fuel(0, diff - 1),
+                diff as u128
+            );
+        }
+
+        #[test]
+        fn fuel_is_symmetric(position: usize, target: usize) {
+            prop_assert_eq!(
+                Part2Triangular.fuel(position, target),
+                Part2Triangular.fuel(target, position)
+            );
+            prop_assert_eq!(Part1.fuel(position, target), position.diff(target) as u128);
+        }
+    }
+}
@@ -1,15 +1,138 @@
+use day_07::{
+    optimal_position, CostModel, FuelCalculator, Part1, Part2Triangular, Piecewise, Quadratic,
+    Weighted,
+};
 use std::fs;
-use day_07::{optimal_position, Part1, Part2Triangular};
+use std::str::FromStr;
+
+const USAGE: &str =
+    "Usage: day-07 [--model linear|triangular|quadratic|piecewise] [--costs FILE] [--weights FILE]";
+
+struct Args {
+    /// Both parts of the puzzle are solved if `None`
+    model: Option<CostModel>,
+    /// One `distance cost` pair per line for the piecewise model
+    costs: Option<String>,
+    /// Comma-separated weights, one per crab
+    weights: Option<String>,
+    help: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            model: None,
+            costs: None,
+            weights: None,
+            help: false,
+        };
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                parsed.help = true;
+                return Ok(parsed);
+            }
+
+            let value = args.next().ok_or(format!("Expected a value for {}", arg))?;
+
+            match arg.as_str() {
+                "--model" => parsed.model = Some(value.parse()?),
+                "--costs" => parsed.costs = Some(value),
+                "--weights" => parsed.weights = Some(value),
+                other => return Err(format!("Unknown argument {}", other)),
+            }
+        }
+
+        match (parsed.model, &parsed.costs) {
+            (Some(CostModel::Piecewise), None) => {
+                Err("--model piecewise requires --costs".to_string())
+            }
+            (Some(CostModel::Piecewise), Some(_)) | (_, None) => Ok(parsed),
+            (_, Some(_)) => Err("--costs requires --model piecewise".to_string()),
+        }
+    }
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn parse_numbers<T: FromStr>(input: &str) -> Result<Vec<T>, String> {
+    input
+        .trim()
+        .split(',')
+        .map(|s| {
+            s.trim()
+                .parse()
+                .map_err(|_| format!("Invalid number {}", s))
+        })
+        .collect()
+}
+
+fn calculator(model: CostModel, costs: Option<&str>) -> Result<Box<dyn FuelCalculator>, String> {
+    Ok(match model {
+        CostModel::Linear => Box::new(Part1),
+        CostModel::Triangular => Box::new(Part2Triangular),
+        CostModel::Quadratic => Box::new(Quadratic),
+        CostModel::Piecewise => Box::new(read(costs.unwrap_or_default())?.parse::<Piecewise>()?),
+    })
+}
 
 fn main() {
-    let numbers: Vec<usize> = fs::read_to_string("./input.txt").unwrap()
-        .split(",")
-        .map(|s| s.parse().unwrap())
-        .collect();
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let numbers: Vec<usize> = parse_numbers(&read("./input.txt")?)?;
+    let weights: Option<Vec<u128>> = match &args.weights {
+        Some(path) => Some(parse_numbers(&read(path)?)?),
+        None => None,
+    };
+
+    if let Some(weights) = &weights {
+        if weights.len() != numbers.len() {
+            return Err(format!(
+                "Expected {} weights, found {}",
+                numbers.len(),
+                weights.len()
+            ));
+        }
+    }
+
+    let calculators: Vec<(String, Box<dyn FuelCalculator>)> = match args.model {
+        Some(model) => vec![(
+            format!("{:?}", model),
+            calculator(model, args.costs.as_deref())?,
+        )],
+        None => vec![
+            ("Part 1".to_string(), Box::new(Part1)),
+            ("Part 2".to_string(), Box::new(Part2Triangular)),
+        ],
+    };
+
+    for (name, calculator) in calculators {
+        let calculator = match &weights {
+            Some(weights) => Box::new(Weighted::new(calculator, weights.clone())),
+            None => calculator,
+        };
 
-    let (position, fuel) = optimal_position::<Part1>(numbers.clone()).unwrap();
-    println!("[Part 1] position: {}, fuel: {}", position, fuel);
+        let (position, fuel) = optimal_position(calculator.as_ref(), numbers.clone())?
+            .ok_or("Expected at least one crab")?;
+        println!("[{}] position: {}, fuel: {}", name, position, fuel);
+    }
 
-    let (position, fuel) = optimal_position::<Part2Triangular>(numbers).unwrap();
-    println!("[Part 2] position: {}, fuel: {}", position, fuel);
+    Ok(())
 }
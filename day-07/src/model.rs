@@ -0,0 +1,260 @@
+use crate::{Diff, FuelCalculator, Strategy};
+use std::str::FromStr;
+
+/// The cost models to choose from at runtime
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CostModel {
+    Linear,
+    Triangular,
+    Quadratic,
+    /// Costs per step that change with the distance, see [`Piecewise`]
+    Piecewise,
+}
+
+impl FromStr for CostModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(CostModel::Linear),
+            "triangular" => Ok(CostModel::Triangular),
+            "quadratic" => Ok(CostModel::Quadratic),
+            "piecewise" => Ok(CostModel::Piecewise),
+            other => Err(format!("Invalid cost model {}", other)),
+        }
+    }
+}
+
+/// Costs growing with the square of the distance
+pub struct Quadratic;
+
+impl FuelCalculator for Quadratic {
+    fn strategy(&self) -> Strategy {
+        Strategy::TernarySearch
+    }
+
+    fn fuel(&self, position: usize, target: usize) -> u128 {
+        (position.diff(target) as u128).pow(2)
+    }
+}
+
+/// Multiplies the fuel of each crab by its own weight
+pub struct Weighted {
+    model: Box<dyn FuelCalculator>,
+    weights: Vec<u128>,
+}
+
+impl Weighted {
+    /// Replaces any weights of `model`, crabs without a weight need its fuel once
+    pub fn new(model: Box<dyn FuelCalculator>, weights: Vec<u128>) -> Self {
+        Self { model, weights }
+    }
+}
+
+impl FuelCalculator for Weighted {
+    /// Weighting keeps convex costs convex, but moves the median and the mean
+    fn strategy(&self) -> Strategy {
+        match self.model.strategy() {
+            Strategy::BruteForce => Strategy::BruteForce,
+            _ => Strategy::TernarySearch,
+        }
+    }
+
+    fn weight(&self, crab: usize) -> u128 {
+        self.weights.get(crab).copied().unwrap_or(1)
+    }
+
+    fn fuel(&self, position: usize, target: usize) -> u128 {
+        self.model.fuel(position, target)
+    }
+}
+
+/// Costs per step that change at certain distances
+///
+/// Parsed from one `distance cost` pair per line, where every step beyond `distance` costs `cost`
+/// until the next pair. The first pair has to start at distance 0.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Piecewise {
+    /// The distance each segment starts at and its cost per step, by increasing distance
+    segments: Vec<(usize, u64)>,
+}
+
+impl Piecewise {
+    pub fn new(segments: Vec<(usize, u64)>) -> Result<Self, String> {
+        match segments.first() {
+            Some((0, _)) => {}
+            Some(_) => return Err("Expected the first segment to start at 0".to_string()),
+            None => return Err("Expected at least one segment".to_string()),
+        }
+
+        if segments.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err("Expected segments by increasing distance".to_string());
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl FromStr for Piecewise {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (distance, cost) = line
+                    .split_once(' ')
+                    .ok_or(format!("Invalid segment {}", line))?;
+
+                Ok((
+                    distance
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid distance {}", distance))?,
+                    cost.trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid cost {}", cost))?,
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Piecewise::new(segments)
+    }
+}
+
+impl FuelCalculator for Piecewise {
+    /// The costs are convex as long as the steps don't get cheaper
+    fn strategy(&self) -> Strategy {
+        if self.segments.windows(2).all(|pair| pair[0].1 <= pair[1].1) {
+            Strategy::TernarySearch
+        } else {
+            Strategy::BruteForce
+        }
+    }
+
+    /// Never overflows, since every step costs less than `2^64`
+    fn fuel(&self, position: usize, target: usize) -> u128 {
+        let distance = position.diff(target);
+        let ends = self.segments.iter().skip(1).map(|(start, _)| *start);
+
+        self.segments
+            .iter()
+            .zip(ends.chain([usize::MAX]))
+            .take_while(|((start, _), _)| *start < distance)
+            .map(|((start, cost), end)| (end.min(distance) - start) as u128 * *cost as u128)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{optimal_position, optimal_position_brute_force, Part1, Part2Triangular};
+    use proptest::prelude::*;
+    use proptest::strategy::Strategy;
+
+    const EXAMPLE: [usize; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+    #[test]
+    fn parses_piecewise_costs() {
+        let piecewise: Piecewise = "0 1\n3 5\n\n10 2\n".parse().unwrap();
+
+        assert_eq!(piecewise.fuel(4, 4), 0);
+        assert_eq!(piecewise.fuel(0, 3), 3);
+        assert_eq!(piecewise.fuel(4, 0), 8);
+        assert_eq!(piecewise.fuel(0, 12), 3 + 7 * 5 + 2 * 2);
+        assert_eq!(piecewise.strategy(), crate::Strategy::BruteForce);
+
+        assert!("1 1".parse::<Piecewise>().is_err());
+        assert!("0 1\n0 2".parse::<Piecewise>().is_err());
+        assert!("0 -1".parse::<Piecewise>().is_err());
+        assert!("0".parse::<Piecewise>().is_err());
+        assert!("".parse::<Piecewise>().is_err());
+    }
+
+    #[test]
+    fn piecewise_costs_never_overflow() {
+        let piecewise = Piecewise::new(vec![(0, 1), (1, u64::MAX)]).unwrap();
+
+        assert_eq!(
+            piecewise.fuel(0, usize::MAX),
+            1 + (usize::MAX as u128 - 1) * u64::MAX as u128
+        );
+    }
+
+    #[test]
+    fn weights_multiply_fuel() {
+        let weighted = Weighted::new(Box::new(Part1), vec![1, 3]);
+
+        // The crab at 10 needs 3 fuel per step, so the cheapest position moves to it
+        assert_eq!(optimal_position(&Part1, vec![0, 10]), Ok(Some((0, 10))));
+        assert_eq!(optimal_position(&weighted, vec![0, 10]), Ok(Some((10, 10))));
+        assert_eq!(weighted.weight(2), 1);
+    }
+
+    #[test]
+    fn runtime_models_solve_example() {
+        let models: [(Box<dyn FuelCalculator>, (usize, u128)); 3] = [
+            (Box::new(Part1), (2, 37)),
+            (Box::new(Part2Triangular), (5, 168)),
+            (Box::new(Piecewise::new(vec![(0, 1)]).unwrap()), (2, 37)),
+        ];
+
+        for (model, expected) in models {
+            assert_eq!(
+                optimal_position(model.as_ref(), EXAMPLE.to_vec()),
+                Ok(Some(expected))
+            );
+
+            let weighted = Weighted::new(model, vec![]);
+            assert_eq!(
+                optimal_position(&weighted, EXAMPLE.to_vec()),
+                Ok(Some(expected))
+            );
+        }
+    }
+
+    fn piecewise() -> impl Strategy<Value = Piecewise> {
+        (
+            0..100_u64,
+            prop::collection::btree_map(1..50_usize, 0..100_u64, 0..5),
+        )
+            .prop_map(|(first, segments)| {
+                let segments = [(0, first)].into_iter().chain(segments).collect();
+                Piecewise::new(segments).unwrap()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn piecewise_matches_brute_force(
+            piecewise in piecewise(),
+            numbers in prop::collection::vec(0..200_usize, 1..30)
+        ) {
+            prop_assert_eq!(
+                optimal_position(&piecewise, numbers.clone()),
+                optimal_position_brute_force(&piecewise, numbers)
+            );
+        }
+
+        #[test]
+        fn weighted_matches_brute_force(
+            crabs in prop::collection::vec((0..500_usize, 0..20_u128), 1..30)
+        ) {
+            let (numbers, weights): (Vec<usize>, Vec<u128>) = crabs.into_iter().unzip();
+            let models: [Box<dyn FuelCalculator>; 3] =
+                [Box::new(Part1), Box::new(Part2Triangular), Box::new(Quadratic)];
+
+            for model in models {
+                let weighted = Weighted::new(model, weights.clone());
+
+                prop_assert_eq!(
+                    optimal_position(&weighted, numbers.clone()),
+                    optimal_position_brute_force(&weighted, numbers.clone())
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::minmax::MinMax;
+use crate::{fuel_consumption, FuelCalculator};
+use num_bigint::BigUint;
+
+/// How to search for the position needing the least fuel
+///
+/// Every strategy returns the smallest of the cheapest positions, as trying every position does.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Strategy {
+    /// Tries every position between the outermost crabs, works for any cost function
+    BruteForce,
+    /// The median, only correct for costs proportional to the distance
+    Median,
+    /// Tries the positions around the mean, only correct for triangular costs
+    Mean,
+    /// Narrows the range down by thirds, only correct for convex costs
+    TernarySearch,
+}
+
+impl Strategy {
+    /// Fails if the cheapest position needs more fuel than fits into `u128`
+    pub fn solve<F: FuelCalculator + ?Sized>(
+        self,
+        calculator: &F,
+        numbers: Vec<usize>,
+    ) -> Result<Option<(usize, u128)>, String> {
+        let (min, max) = match numbers.iter().min_max() {
+            Some((min, max)) => (*min, *max),
+            None => return Ok(None),
+        };
+
+        let cheapest = match self {
+            Strategy::BruteForce => cheapest(calculator, &numbers, min..=max),
+            Strategy::Median => {
+                let middle = (numbers.len() - 1) / 2;
+                // Selecting on a copy keeps the crabs in the order their weights refer to
+                let median = *numbers.clone().select_nth_unstable(middle).1;
+
+                Some((median, fuel_consumption(calculator, &numbers, median)))
+            }
+            Strategy::Mean => {
+                // The optimum lies within half a position of the mean
+                let sum: u128 = numbers.iter().map(|n| *n as u128).sum();
+                let count = numbers.len() as u128;
+                let low = (sum / count).saturating_sub(1).max(min as u128);
+                let high = (sum.div_ceil(count) + 1).min(max as u128);
+
+                cheapest(calculator, &numbers, low as usize..=high as usize)
+            }
+            Strategy::TernarySearch => ternary_search(calculator, &numbers, min, max),
+        };
+
+        cheapest
+            .map(|(position, fuel)| fuel.map(|fuel| (position, fuel)))
+            .transpose()
+    }
+}
+
+/// Orders overflowing amounts of fuel after all others
+fn rank(fuel: &Result<u128, String>) -> (bool, u128) {
+    match fuel {
+        Ok(fuel) => (false, *fuel),
+        Err(_) => (true, 0),
+    }
+}
+
+/// The fuel needed to get to `x`, comparable exactly even where it overflows
+///
+/// Overflowing amounts order after all others, since `Ok` orders before `Err`.
+fn exact_fuel<F: FuelCalculator + ?Sized>(
+    calculator: &F,
+    numbers: &[usize],
+    x: usize,
+) -> Result<u128, BigUint> {
+    fuel_consumption(calculator, numbers, x).map_err(|_| {
+        numbers
+            .iter()
+            .enumerate()
+            .map(|(crab, n)| BigUint::from(calculator.fuel(*n, x)) * calculator.weight(crab))
+            .sum()
+    })
+}
+
+fn cheapest<F: FuelCalculator + ?Sized>(
+    calculator: &F,
+    numbers: &[usize],
+    positions: impl Iterator<Item = usize>,
+) -> Option<(usize, Result<u128, String>)> {
+    positions
+        .map(|position| (position, fuel_consumption(calculator, numbers, position)))
+        .min_by_key(|(_, fuel)| rank(fuel))
+}
+
+fn ternary_search<F: FuelCalculator + ?Sized>(
+    calculator: &F,
+    numbers: &[usize],
+    mut low: usize,
+    mut high: usize,
+) -> Option<(usize, Result<u128, String>)> {
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (left, right) = (low + third, high - third);
+
+        let left_fuel = exact_fuel(calculator, numbers, left);
+        let right_fuel = exact_fuel(calculator, numbers, right);
+
+        // On ties, the cheapest positions may continue to the left of `left`
+        if left_fuel < right_fuel {
+            high = right - 1;
+        } else if left_fuel > right_fuel {
+            low = left + 1;
+        } else {
+            high = right;
+        }
+    }
+
+    cheapest(calculator, numbers, low..=high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        optimal_position, Part1, Part2ManualSum, Part2Triangular, Quadratic, Strategy, Weighted,
+    };
+    use proptest::prelude::*;
+
+    const EXAMPLE: [usize; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+    #[test]
+    fn strategies_solve_example() {
+        for strategy in [
+            Strategy::BruteForce,
+            Strategy::Median,
+            Strategy::TernarySearch,
+        ] {
+            assert_eq!(strategy.solve(&Part1, EXAMPLE.to_vec()), Ok(Some((2, 37))));
+        }
+
+        for strategy in [
+            Strategy::BruteForce,
+            Strategy::Mean,
+            Strategy::TernarySearch,
+        ] {
+            assert_eq!(
+                strategy.solve(&Part2Triangular, EXAMPLE.to_vec()),
+                Ok(Some((5, 168)))
+            );
+        }
+
+        assert_eq!(Strategy::Median.solve(&Part1, vec![]), Ok(None));
+        assert_eq!(
+            Strategy::Mean.solve(&Part2Triangular, vec![7]),
+            Ok(Some((7, 0)))
+        );
+    }
+
+    #[test]
+    fn picks_the_smallest_of_tied_positions() {
+        // Every position from 1 to 5 needs 4 fuel
+        let numbers = vec![1, 5];
+
+        for strategy in [Strategy::Median, Strategy::TernarySearch] {
+            assert_eq!(strategy.solve(&Part1, numbers.clone()), Ok(Some((1, 4))));
+        }
+    }
+
+    #[test]
+    fn fails_if_the_cheapest_position_overflows() {
+        // Halfway between the crabs, the fuel needed adds up to exactly 2^128
+        let numbers = [[0; 4], [usize::MAX; 4]].concat();
+
+        for strategy in [Strategy::Mean, Strategy::TernarySearch] {
+            assert!(strategy.solve(&Part2Triangular, numbers.clone()).is_err());
+        }
+        assert!(Strategy::Median.solve(&Part1, numbers).is_ok());
+    }
+
+    #[test]
+    fn skips_overflowing_positions() {
+        let numbers = vec![0, usize::MAX, usize::MAX, usize::MAX, usize::MAX];
+        assert!(fuel_consumption(&Part2Triangular, &numbers, 0).is_err());
+
+        let expected = Strategy::TernarySearch.solve(&Part2Triangular, numbers.clone());
+        assert!(expected.as_ref().is_ok_and(Option::is_some));
+        assert_eq!(Strategy::Mean.solve(&Part2Triangular, numbers), expected);
+
+        // Both probes of the first step overflow, with the only finite positions to the right
+        let numbers = [vec![0], vec![usize::MAX; 19]].concat();
+        assert!(fuel_consumption(&Part2Triangular, &numbers, usize::MAX / 3).is_err());
+        assert!(fuel_consumption(&Part2Triangular, &numbers, usize::MAX / 3 * 2).is_err());
+
+        let expected = Strategy::Mean.solve(&Part2Triangular, numbers.clone());
+        assert!(expected.as_ref().is_ok_and(Option::is_some));
+        assert_eq!(
+            Strategy::TernarySearch.solve(&Part2Triangular, numbers.clone()),
+            expected
+        );
+        assert_eq!(
+            optimal_position(&Quadratic, numbers.clone()),
+            Strategy::Mean.solve(&Quadratic, numbers)
+        );
+
+        // The weighted optimum lies within a position of the weighted mean
+        let weighted = Weighted::new(Box::new(Part2Triangular), vec![1, 19]);
+        let numbers = vec![0, usize::MAX];
+        let mean = (usize::MAX as u128 * 19 / 20) as usize;
+        let (position, fuel) = cheapest(&weighted, &numbers, mean - 1..=mean + 1).unwrap();
+
+        assert_eq!(
+            optimal_position(&weighted, numbers),
+            Ok(Some((position, fuel.unwrap())))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn fast_strategies_agree_on_wide_ranges(
+            numbers in prop::collection::vec(0..=usize::MAX / 2, 1..20)
+        ) {
+            prop_assert_eq!(
+                Strategy::Median.solve(&Part1, numbers.clone()),
+                Strategy::TernarySearch.solve(&Part1, numbers.clone())
+            );
+            prop_assert_eq!(
+                Strategy::Mean.solve(&Part2Triangular, numbers.clone()),
+                Strategy::TernarySearch.solve(&Part2Triangular, numbers)
+            );
+        }
+
+        #[test]
+        fn strategies_match_brute_force(numbers in prop::collection::vec(0..2000_usize, 1..50)) {
+            let part_1 = Ok(Strategy::BruteForce.solve(&Part1, numbers.clone()).unwrap());
+            prop_assert_eq!(Strategy::Median.solve(&Part1, numbers.clone()), part_1.clone());
+            prop_assert_eq!(Strategy::TernarySearch.solve(&Part1, numbers.clone()), part_1);
+
+            let part_2 = Ok(Strategy::BruteForce.solve(&Part2Triangular, numbers.clone()).unwrap());
+            prop_assert_eq!(Strategy::Mean.solve(&Part2Triangular, numbers.clone()), part_2.clone());
+            prop_assert_eq!(Strategy::Mean.solve(&Part2ManualSum, numbers.clone()), part_2.clone());
+            prop_assert_eq!(
+                Strategy::TernarySearch.solve(&Part2Triangular, numbers.clone()),
+                part_2
+            );
+
+            prop_assert_eq!(
+                Strategy::TernarySearch.solve(&Quadratic, numbers.clone()),
+                Strategy::BruteForce.solve(&Quadratic, numbers)
+            );
+        }
+    }
+}